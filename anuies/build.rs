@@ -1,3 +1,3 @@
 fn main() {
     embed_resource::compile("program.rc", embed_resource::NONE);
-}
\ No newline at end of file
+}
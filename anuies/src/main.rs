@@ -7,12 +7,17 @@
 
 use calamine::{open_workbook, Data, DataRef, DataType, Range, Reader};
 use clap::Parser;
-use dialogs::{ask_open_file, ask_open_files, ask_save_file};
+use dialogs::{
+    ask_args, ask_open_file_with, ask_open_files_with, ask_save_file_with, clear_history,
+    install_panic_hook, msg, report_error, set_lang, set_log_dir, show_message, wants_form, Lang,
+    Level, Text,
+};
 use itertools::{chain, izip, Itertools};
 use rust_xlsxwriter::Workbook;
 use std::collections::{HashMap, HashSet};
 use std::ops::Not;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use strings::{institutions::Institutions, periodo::Period, safe_names::file_path, NormMap};
 
 const UNI: &str = "NOMBRE INSTITUCIÓN";
 const CAMPO_AMPLIO: &str = "CAMPO AMPLIO DE FORMACIÓN";
@@ -22,8 +27,11 @@ const NIVEL: &str = "NIVEL DE ESTUDIOS";
 
 #[derive(Debug)]
 struct Config {
+    /// Nombres normalizados por la llave canónica de cada variante del nombre
     universidades: HashMap<String, String>,
-    niveles_activos: HashMap<String, bool>,
+    instituciones: Institutions,
+    /// Niveles activos sin importar mayúsculas, acentos o espacios
+    niveles_activos: NormMap<bool>,
 }
 
 #[derive(Parser)]
@@ -38,6 +46,169 @@ struct Args {
     /// El excel con el mapeo de las universidades y los niveles activos
     #[clap(short, long)]
     config: Option<PathBuf>,
+
+    /// Olvidar las carpetas y archivos elegidos antes en los diálogos
+    #[clap(long)]
+    olvidar: bool,
+
+    /// Idioma de los mensajes, `es` o `en`. Por defecto el de `DIALOGS_LANG` o el del sistema, y
+    /// español si no hay ninguno
+    #[clap(long)]
+    idioma: Option<Lang>,
+}
+
+const SIN_CONFIG: Text = Text::new(
+    "No se ha especificado el archivo de universidades",
+    "The universities file wasn't specified",
+);
+const SIN_RUTAS: Text = Text::new("No se han especificado rutas", "No paths were specified");
+const SIN_SALIDA: Text = Text::new(
+    "No se ha especificado un archivo de salida",
+    "The output file wasn't specified",
+);
+const SELECCIONA_CONFIG: Text = Text::new(
+    "Selecciona el archivo de configuration en Excel",
+    "Select the Excel configuration file",
+);
+const SELECCIONA_RUTAS: Text = Text::new(
+    "Selecciona los archivos de ANUIES a procesar",
+    "Select the ANUIES files to process",
+);
+const SELECCIONA_SALIDA: Text = Text::new(
+    "Selecciona cómo almacenar el resultado",
+    "Select where to store the result",
+);
+const NO_XLSX: Text = Text::new("No es un archivo .xlsx", "It isn't an .xlsx file");
+const NO_ABRE: Text = Text::new(
+    "No se pudo abrir como Excel: {}",
+    "It couldn't be opened as Excel: {}",
+);
+const NO_SE_PUDO_ABRIR: Text = Text::new("No se pudo abrir {}", "{} couldn't be opened");
+const NO_SE_PUDO_GUARDAR: Text = Text::new("No se pudo guardar {}", "{} couldn't be saved");
+const SIN_HOJA: Text = Text::new(
+    "Le falta una hoja con «{}» en el nombre",
+    "It lacks a sheet with “{}” in its name",
+);
+const SIN_COLUMNA: Text = Text::new(
+    "Le falta la columna {} en la cuarta fila de su primera hoja",
+    "It lacks the column {} in the fourth row of its first sheet",
+);
+const ENCABEZADO_NO_TEXTO: Text = Text::new(
+    "La celda de la columna {} en la cuarta fila de su primera hoja no es texto",
+    "The cell of the column {} in the fourth row of its first sheet isn't text",
+);
+const FILA_UNI: Text = Text::new(
+    "La fila {} de la hoja de universidades no tiene el nombre y el nombre normalizado como texto",
+    "The row {} of the universities sheet doesn't have the name and the normalized name as text",
+);
+const FILA_NIVEL: Text = Text::new(
+    "La fila {} de la hoja de niveles no tiene el nivel como texto y si está activo como texto o número",
+    "The row {} of the levels sheet doesn't have the level as text and whether it's active as text or number",
+);
+
+/// Que sea un `.xlsx`, sin revisar que exista
+fn validar_xlsx(ruta: &Path) -> Result<(), String> {
+    match ruta.extension().and_then(std::ffi::OsStr::to_str) {
+        Some("xlsx") => Ok(()),
+        _ => Err(NO_XLSX.to_string()),
+    }
+}
+
+fn validar_abre(ruta: &Path) -> Result<calamine::Xlsx<std::io::BufReader<std::fs::File>>, String> {
+    validar_xlsx(ruta)?;
+    open_workbook(ruta).map_err(|err: calamine::XlsxError| NO_ABRE.fill(err))
+}
+
+/// Fila de la hoja de universidades: nombre y nombre normalizado
+fn fila_uni(row: &[Data]) -> bool {
+    matches!(row, [Data::String(_), Data::String(_), ..])
+}
+
+/// Fila de la hoja de niveles: nivel y si está activo
+fn fila_nivel(row: &[Data]) -> bool {
+    matches!(
+        row,
+        [
+            Data::String(_),
+            Data::String(_) | Data::Int(_) | Data::Float(_),
+            ..
+        ]
+    )
+}
+
+/// Filas que lee [`read_config_xlsx`], las que no empiezan vacías
+fn filas_config(range: &Range<Data>) -> impl Iterator<Item = (usize, &[Data])> {
+    let inicio = range.start().map_or(0, |(fila, _)| fila as usize);
+    range
+        .rows()
+        .enumerate()
+        .filter(|(_, row)| row.first().is_some_and(|first| first.is_empty().not()))
+        .map(move |(i, row)| (inicio + i + 1, row))
+}
+
+/// Que tenga las hojas de universidades y niveles que lee [`read_config_xlsx`] con filas que
+/// pueda leer
+fn validar_config(ruta: &Path) -> Result<(), String> {
+    let mut xl = validar_abre(ruta)?;
+    let sheets = xl.sheet_names();
+
+    let hojas = [
+        ("uni", fila_uni as fn(&[Data]) -> bool, FILA_UNI),
+        ("nivel", fila_nivel, FILA_NIVEL),
+    ];
+    for (hoja, valida, error) in hojas {
+        let Some(nombre) = sheets.iter().find(|a| a.to_lowercase().contains(hoja)) else {
+            return Err(SIN_HOJA.fill(hoja));
+        };
+        let range = xl
+            .worksheet_range(nombre)
+            .map_err(|err| NO_ABRE.fill(err))?;
+
+        let invalida = filas_config(&range).find(|(_, row)| !valida(row));
+        if let Some((fila, _)) = invalida {
+            return Err(error.fill(fila));
+        }
+    }
+
+    Ok(())
+}
+
+/// Que tenga en su primera hoja los encabezados que leen [`get_headers`] y el procesamiento, todos
+/// como texto
+fn validar_anuies(ruta: &Path) -> Result<(), String> {
+    let mut xl = validar_abre(ruta)?;
+    let sheets = xl.sheet_names();
+    let range = sheets
+        .first()
+        .and_then(|name| xl.worksheet_range_ref(name).ok());
+    let encabezados = range
+        .as_ref()
+        .and_then(|range| range.rows().nth(3))
+        .unwrap_or_default();
+
+    for columna in [UNI, NIVEL, CAMPO_AMPLIO, CAMPO_ESPECIFICO, CAMPO_DETALLADO] {
+        if !encabezados.iter().any(|v| v.get_string() == Some(columna)) {
+            return Err(SIN_COLUMNA.fill(columna));
+        }
+    }
+
+    if let Some(i) = encabezados.iter().position(|v| v.get_string().is_none()) {
+        let inicio = range
+            .as_ref()
+            .and_then(Range::start)
+            .map_or(0, |(_, columna)| columna as usize);
+        return Err(ENCABEZADO_NO_TEXTO.fill(inicio + i + 1));
+    }
+
+    Ok(())
+}
+
+/// Sale con un error si `ruta`, dada en la línea de comandos, no es válida
+fn validar_cli(ruta: &Path, validar: impl Fn(&Path) -> Result<(), String>) {
+    if let Err(err) = validar(ruta) {
+        eprintln!("{}: {err}", ruta.display());
+        std::process::exit(1);
+    }
 }
 
 type Related<'a> = HashMap<(&'a str, &'a str, &'a str, &'a str), u64>;
@@ -170,8 +341,9 @@ fn read_config_xlsx(ruta: &std::path::Path) -> Config {
     let sheets = xl.sheet_names();
     let sheets_lower = sheets.iter().map(|a| a.to_lowercase()).collect_vec();
 
+    let instituciones = Institutions::new();
     let mut universidades = HashMap::new();
-    let mut niveles_activos = HashMap::new();
+    let mut niveles_activos = NormMap::new();
 
     let (i_uni, _) = sheets_lower
         .iter()
@@ -185,34 +357,28 @@ fn read_config_xlsx(ruta: &std::path::Path) -> Config {
     let uni = xl.worksheet_range(&sheets[i_uni]).unwrap();
     let nivel = xl.worksheet_range(&sheets[i_nivel]).unwrap();
 
-    for row in uni
-        .rows()
-        .filter(|a| a.first().is_some_and(|first| first.is_empty().not()))
-    {
+    for (_, row) in filas_config(&uni) {
         match row {
             [Data::String(uni), Data::String(normalized), ..] => {
-                universidades.insert(uni.to_string(), normalized.to_string());
+                universidades.insert(instituciones.key(uni), normalized.clone());
             }
             _ => unreachable!(),
         }
     }
 
-    for row in nivel
-        .rows()
-        .filter(|a| a.first().is_some_and(|first| first.is_empty().not()))
-    {
+    for (_, row) in filas_config(&nivel) {
         match row {
             [Data::String(nivel), Data::String(activo), ..] => {
                 niveles_activos.insert(
-                    nivel.to_string(),
+                    nivel.clone(),
                     ["si", "sí", "0"].contains(&activo.to_lowercase().as_str()),
                 );
             }
             [Data::String(nivel), Data::Int(activo), ..] => {
-                niveles_activos.insert(nivel.to_string(), *activo != 0);
+                niveles_activos.insert(nivel.clone(), *activo != 0);
             }
             [Data::String(nivel), Data::Float(activo), ..] => {
-                niveles_activos.insert(nivel.to_string(), *activo != 0.);
+                niveles_activos.insert(nivel.clone(), *activo != 0.);
             }
             _ => unreachable!(),
         }
@@ -220,29 +386,60 @@ fn read_config_xlsx(ruta: &std::path::Path) -> Config {
 
     Config {
         universidades,
+        instituciones,
         niveles_activos,
     }
 }
 
 fn main() {
+    // Sin argumentos en una terminal se piden todos en un formulario en lugar de un diálogo por
+    // parámetro
+    let args = if wants_form() {
+        let Some(args) = ask_args::<Args>(&["olvidar"]) else {
+            return;
+        };
+        args
+    } else {
+        Args::parse()
+    };
+
     let Args {
         rutas,
         salida,
         config,
-    } = Args::parse();
+        olvidar,
+        idioma,
+    } = args;
+
+    if let Some(idioma) = idioma {
+        set_lang(idioma);
+    }
+
+    if olvidar {
+        if let Err(err) = clear_history() {
+            eprintln!("{}", msg::HISTORY_NOT_CLEARED.fill(err));
+        }
+    }
 
     let from_cli = !rutas.is_empty() || salida.is_some() || config.is_some();
 
+    // Sin consola los pánicos se pierden, así que se muestran en una ventana
+    if !from_cli {
+        install_panic_hook();
+    }
+
     if from_cli && config.is_none() {
-        eprintln!("No se ha especificado el archivo de universidades");
+        eprintln!("{SIN_CONFIG}");
         std::process::exit(1);
     }
     let config = if let Some(config) = config {
+        validar_cli(&config, validar_config);
         config
     } else {
-        match ask_open_file(
-            "Selecciona el archivo de configuration en Excel",
+        match ask_open_file_with(
+            SELECCIONA_CONFIG,
             Some(&[("Excel", &["xlsx"])]),
+            validar_config,
         ) {
             Some(path) => path,
             None => return,
@@ -252,36 +449,46 @@ fn main() {
     let config = read_config_xlsx(&config);
 
     if rutas.is_empty() && from_cli {
-        eprintln!("No se han especificado rutas");
+        eprintln!("{SIN_RUTAS}");
         std::process::exit(1);
     }
     let rutas = if rutas.is_empty() {
-        match ask_open_files(
-            "Selecciona los archivos de ANUIES a procesar",
+        match ask_open_files_with(
+            SELECCIONA_RUTAS,
             Some(&[("Excel", &["xlsx"])]),
+            validar_anuies,
         ) {
             Some(path) => path,
             None => return,
         }
     } else {
+        for ruta in &rutas {
+            validar_cli(ruta, validar_anuies);
+        }
         rutas
     };
 
     if salida.is_none() && from_cli {
-        eprintln!("No se ha especificado un archivo de salida");
+        eprintln!("{SIN_SALIDA}");
         std::process::exit(1);
     }
     let output = if let Some(output) = salida {
+        validar_cli(&output, validar_xlsx);
         output
     } else {
-        match ask_save_file(
-            "Selecciona cómo almacenar el resultado",
+        match ask_save_file_with(
+            SELECCIONA_SALIDA,
             Some(&[("Excel", &["xlsx"])]),
+            validar_xlsx,
         ) {
             Some(path) => path,
             None => return,
         }
     };
+    let output = file_path(&output);
+    if let Some(dir) = output.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        set_log_dir(dir);
+    }
 
     let mut workbook = Workbook::new();
     let worksheet = workbook.add_worksheet();
@@ -306,11 +513,10 @@ fn main() {
 
         // Obtenemos el ciclo
         let ciclo = rows.next().unwrap().first().unwrap();
-        let ciclo = if let Some(ciclo) = ciclo.get_string() {
-            &ciclo.split_once("Ciclo escolar ").unwrap().1[0..9]
-        } else {
-            "NONE"
-        };
+        let ciclo = ciclo
+            .get_string()
+            .and_then(Period::find)
+            .map_or_else(|| "NONE".to_string(), |ciclo| ciclo.to_string());
 
         // Siempre hay una linea despues del título y el encabezado
         rows.next();
@@ -324,7 +530,7 @@ fn main() {
             // Normalizamos el nivel de la universidad
             let uni = get_uni_name(
                 row[get_relevant_id(headers, UNI)].get_string().unwrap(),
-                &config.universidades,
+                &config,
             );
             // Normalizamos el nivel del programa
             let nivel = get_nivel_name(row[get_relevant_id(headers, NIVEL)].get_string().unwrap());
@@ -347,7 +553,7 @@ fn main() {
                 continue;
             }
 
-            worksheet.write(c_row, 0, ciclo).unwrap();
+            worksheet.write(c_row, 0, ciclo.as_str()).unwrap();
             for (c_col, v_col) in row.iter().enumerate() {
                 let c_col: u16 = c_col.try_into().unwrap();
                 match c_col {
@@ -401,11 +607,11 @@ fn main() {
         }
     }
 
-    workbook.save(&output).unwrap();
-    rfd::MessageDialog::new()
-        .set_level(rfd::MessageLevel::Info)
-        .set_title("Listo")
-        .show();
+    if let Err(err) = workbook.save(&output) {
+        report_error(&NO_SE_PUDO_GUARDAR.fill(output.display()), &err.to_string());
+        std::process::exit(1);
+    }
+    show_message(Level::Info, msg::DONE.get(), "");
 }
 
 fn make_persistent<'a>(strings: &'a mut HashSet<String>, text: &str) -> &'a str {
@@ -437,20 +643,22 @@ fn get_relevant_id_u16(headers: &[String], name: &str) -> u16 {
 }
 
 fn open_excel(ruta: &std::path::Path) -> calamine::Xlsx<std::io::BufReader<std::fs::File>> {
-    match ruta.extension().and_then(std::ffi::OsStr::to_str) {
-        Some("xlsx") => (),
-        _ => panic!("Expecting an excel file"),
-    }
-    open_workbook(ruta).unwrap()
+    validar_abre(ruta).unwrap_or_else(|err| {
+        report_error(&NO_SE_PUDO_ABRIR.fill(ruta.display()), &err);
+        std::process::exit(1)
+    })
 }
 
 #[allow(dead_code)]
-fn get_uni_name<'a>(raw: &str, config: &'a HashMap<String, String>) -> &'a str {
-    config.get(raw.trim()).map_or("Otras", |a| a.as_str())
+fn get_uni_name<'a>(raw: &str, config: &'a Config) -> &'a str {
+    config
+        .universidades
+        .get(&config.instituciones.key(raw))
+        .map_or("Otras", |a| a.as_str())
 }
 
-fn nivel_activo(raw: &str, config: &HashMap<String, bool>) -> bool {
-    config.get(raw.trim()).is_some_and(|activo| *activo)
+fn nivel_activo(raw: &str, config: &NormMap<bool>) -> bool {
+    config.get(raw).is_some_and(|activo| *activo)
 }
 
 fn get_nivel_name(raw: &str) -> &str {
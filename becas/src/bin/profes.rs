@@ -1,5 +1,5 @@
 use polars::{lazy::dsl::*, prelude::*};
-use polars_excel_writer::PolarsXlsxWriter;
+use polars_sheet_reader::{read_csv_with, write_xlsx, write_xlsx_with, ReadOptions, WriteOptions};
 use std::{path::PathBuf, str::FromStr};
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -10,23 +10,29 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         return Ok(());
     };
 
-    let df = polars::prelude::CsvReader::from_path(ruta)?
-        .has_header(true)
-        .infer_schema(None)
-        .finish()?;
+    // Los porcentajes se suman como decimales para que 33.33 + 66.67 sea exactamente 100
+    let opciones = ReadOptions::default().with_decimal("Porcentaje de beca", 2);
+    let df = read_csv_with(ruta, &opciones)?;
 
     let df = df
         .lazy()
         .group_by(["Institución", "Grado Académico", "ID"])
         .agg([col("Porcentaje de beca").sum().alias("Porcentaje total")])
         .collect()?;
-    write_xlsx(&df, "suma_todos.xlsx")?;
+    // Son porcentajes, no dinero, así que no llevan el formato de moneda de los decimales
+    let formatos = WriteOptions::default().with_format("Porcentaje total", "0.00");
+    write_xlsx_with(&df, "suma_todos.xlsx", &formatos)?;
 
     let df = df
         .lazy()
-        .filter(col("Porcentaje total").gt_eq(lit(200)))
+        // polars no compara decimales, la suma ya es exacta y 200 se representa igual como flotante
+        .filter(
+            col("Porcentaje total")
+                .cast(DataType::Float64)
+                .gt_eq(lit(200)),
+        )
         .collect()?;
-    write_xlsx(&df, "solo_200s.xlsx")?;
+    write_xlsx_with(&df, "solo_200s.xlsx", &formatos)?;
 
     let df = df
         .lazy()
@@ -40,15 +46,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         )
         .collect()?;
 
-    write_xlsx(&df, "dataframe.xlsx")?;
-
-    Ok(())
-}
-
-fn write_xlsx(df: &DataFrame, name: &str) -> Result<(), Box<dyn std::error::Error>> {
-    let mut writer = PolarsXlsxWriter::new();
-    writer.write_dataframe(df)?;
-    writer.save(name)?;
+    write_xlsx(&df, "dataframe.xlsx", false)?;
 
     Ok(())
 }
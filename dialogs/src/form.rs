@@ -0,0 +1,784 @@
+use clap::{Arg, ArgAction, ArgMatches, Command, CommandFactory, FromArgMatches, ValueHint};
+use ratatui::{
+    backend::CrosstermBackend,
+    crossterm::{
+        event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+        execute,
+        terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+    },
+    layout::{Constraint, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Paragraph, Wrap},
+    Frame, Terminal,
+};
+use rustyline::completion::FilenameCompleter;
+use std::{
+    ffi::OsString,
+    io::{self, Stderr},
+};
+
+use crate::{msg, terminal::parse_path};
+
+/// One screen in the terminal with every argument of a tool, to fill them at once instead of
+/// answering a question after another
+///
+/// The fields are made from the arguments of the clap [`Command`]: flags are checkboxes, arguments
+/// with possible values are chosen with the arrows and the rest are written, paths with tab
+/// completion. Multiple values are separated by spaces, quoting the ones with spaces. Each field is
+/// checked with its parser when the form is run and the errors are shown next to it.
+#[derive(Debug)]
+pub struct Form {
+    command: Command,
+    fields: Vec<Field>,
+    /// Field being edited, the run button after the last one
+    selected: usize,
+    /// Why the form can't be run
+    error: Option<String>,
+    /// Paths that complete the selected field
+    candidates: Vec<String>,
+}
+
+/// What a field holds
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Value {
+    /// A flag, given when it differs from its default
+    Flag { set: bool, default: bool },
+    /// One of the possible values, none to use the default
+    Choice {
+        options: Vec<String>,
+        selected: Option<usize>,
+    },
+    /// Written by the user, like numbers or paths
+    Text { text: String, cursor: usize },
+}
+
+#[derive(Debug, Clone)]
+struct Field {
+    arg: Arg,
+    label: String,
+    help: String,
+    value: Value,
+    error: Option<String>,
+}
+
+impl Form {
+    /// Form with a field for every argument of `command`, except help and version
+    #[must_use]
+    pub fn new(mut command: Command) -> Self {
+        command.build();
+        let fields = command.get_arguments().filter_map(Field::new).collect();
+
+        Self {
+            command,
+            fields,
+            selected: 0,
+            error: None,
+            candidates: Vec::new(),
+        }
+    }
+
+    /// Leave out the arguments with these ids, like the ones that only make sense in scripts
+    #[must_use]
+    pub fn skip(mut self, ids: &[&str]) -> Self {
+        self.fields
+            .retain(|field| !ids.contains(&field.arg.get_id().as_str()));
+        self
+    }
+
+    /// Command line with the values of the fields, as if the user wrote it
+    #[must_use]
+    pub fn args(&self) -> Vec<OsString> {
+        let mut args = vec![OsString::from(self.command.get_name())];
+        let (positionals, named) = self
+            .fields
+            .iter()
+            .partition::<Vec<_>, _>(|field| field.arg.is_positional());
+
+        for field in named {
+            args.extend(field.args());
+        }
+        if positionals.iter().any(|field| !field.values().is_empty()) {
+            args.push("--".into());
+            for field in positionals {
+                args.extend(field.values());
+            }
+        }
+
+        args
+    }
+
+    /// Show the form until it is run with valid values, returning the arguments parsed, or until
+    /// it is cancelled, returning None
+    ///
+    /// # Errors
+    ///
+    /// If the terminal can't be used
+    pub fn ask<T: FromArgMatches>(mut self) -> io::Result<Option<T>> {
+        let mut screen = Screen::new()?;
+
+        loop {
+            screen.0.draw(|frame| self.draw(frame))?;
+
+            let Event::Key(key) = event::read()? else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+
+            match self.key(key) {
+                Some(Action::Cancel) => return Ok(None),
+                Some(Action::Run) => {
+                    let Some(matches) = self.check() else {
+                        continue;
+                    };
+                    match T::from_arg_matches(&matches) {
+                        Ok(args) => return Ok(Some(args)),
+                        Err(err) => self.error = Some(first_line(&err)),
+                    }
+                }
+                None => (),
+            }
+        }
+    }
+
+    /// Check every field and then all of them together, like the required ones
+    fn check(&mut self) -> Option<ArgMatches> {
+        let mut valid = true;
+        for field in &mut self.fields {
+            field.error = field.check().err();
+            valid &= field.error.is_none();
+        }
+        if !valid {
+            self.error = Some(msg::FORM_INVALID.get().to_string());
+            return None;
+        }
+
+        match self.command.clone().try_get_matches_from(self.args()) {
+            Ok(matches) => {
+                self.error = None;
+                Some(matches)
+            }
+            Err(err) => {
+                self.error = Some(first_line(&err));
+                None
+            }
+        }
+    }
+
+    fn key(&mut self, key: KeyEvent) -> Option<Action> {
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Esc => return Some(Action::Cancel),
+            KeyCode::Char('c') if ctrl => return Some(Action::Cancel),
+            KeyCode::Up | KeyCode::BackTab => self.select(self.selected.saturating_sub(1)),
+            KeyCode::Enter if self.selected >= self.fields.len() => return Some(Action::Run),
+            KeyCode::Down | KeyCode::Enter => self.select(self.selected + 1),
+            KeyCode::Tab => match self.fields.get_mut(self.selected) {
+                Some(field) if field.is_path() => self.candidates = field.complete(),
+                _ => self.select(self.selected + 1),
+            },
+            _ => {
+                if let Some(field) = self.fields.get_mut(self.selected) {
+                    field.key(key);
+                }
+            }
+        }
+
+        None
+    }
+
+    fn select(&mut self, selected: usize) {
+        self.selected = selected.min(self.fields.len());
+        self.candidates.clear();
+    }
+
+    fn draw(&self, frame: &mut Frame<'_>) {
+        let [main, help, keys] = Layout::vertical([
+            Constraint::Min(3),
+            Constraint::Length(4),
+            Constraint::Length(1),
+        ])
+        .areas(frame.size());
+
+        let title = match self.command.get_about() {
+            Some(about) => format!(" {} · {about} ", self.command.get_name()),
+            None => format!(" {} ", self.command.get_name()),
+        };
+        let block = Block::bordered().title(title);
+        let inner = block.inner(main);
+        frame.render_widget(block, main);
+
+        let label_width = self
+            .fields
+            .iter()
+            .map(|field| field.label.chars().count())
+            .max()
+            .unwrap_or_default()
+            .min(usize::from(inner.width / 2));
+        let value_x = inner.x + u16::try_from(label_width).unwrap_or(0) + 2;
+        let value_width = usize::from(inner.right().saturating_sub(value_x));
+
+        let mut lines = Vec::new();
+        for (i, field) in self.fields.iter().enumerate() {
+            let selected = i == self.selected;
+            let (value, cursor) = field.show(value_width);
+            let style = if selected {
+                Style::new().add_modifier(Modifier::REVERSED)
+            } else {
+                Style::new()
+            };
+            let label = Style::new().fg(if field.error.is_some() {
+                Color::Red
+            } else {
+                Color::Cyan
+            });
+
+            if let (true, Some(cursor)) = (selected, cursor) {
+                let y = inner.y + u16::try_from(lines.len()).unwrap_or(u16::MAX);
+                if y < inner.bottom() {
+                    let x = value_x + u16::try_from(cursor).unwrap_or(0);
+                    frame.set_cursor(x.min(inner.right().saturating_sub(1)), y);
+                }
+            }
+            lines.push(Line::from(vec![
+                Span::styled(format!("{:label_width$}  ", field.label), label),
+                Span::styled(value, style),
+            ]));
+            if let Some(error) = &field.error {
+                lines.push(Line::styled(
+                    format!("{:label_width$}  {error}", ""),
+                    Style::new().fg(Color::Red),
+                ));
+            }
+        }
+        lines.push(Line::default());
+        let run = if self.selected >= self.fields.len() {
+            Style::new().add_modifier(Modifier::REVERSED)
+        } else {
+            Style::new()
+        };
+        lines.push(Line::styled(format!("[ {} ]", msg::RUN), run));
+        frame.render_widget(Paragraph::new(lines), inner);
+
+        let text = if let Some(error) = &self.error {
+            Line::styled(error.as_str(), Style::new().fg(Color::Red))
+        } else if !self.candidates.is_empty() {
+            Line::raw(self.candidates.join("  "))
+        } else {
+            Line::raw(
+                self.fields
+                    .get(self.selected)
+                    .map_or("", |f| f.help.as_str()),
+            )
+        };
+        frame.render_widget(
+            Paragraph::new(text).wrap(Wrap { trim: true }),
+            Rect {
+                x: help.x + 1,
+                width: help.width.saturating_sub(2),
+                ..help
+            },
+        );
+        frame.render_widget(
+            Paragraph::new(msg::FORM_KEYS.get()).style(Style::new().fg(Color::DarkGray)),
+            keys,
+        );
+    }
+}
+
+enum Action {
+    Run,
+    Cancel,
+}
+
+impl Field {
+    /// Field of `arg`, None for the ones that can't be written like help and counters
+    fn new(arg: &Arg) -> Option<Self> {
+        if arg.is_hide_set() {
+            return None;
+        }
+
+        let default = arg
+            .get_default_values()
+            .iter()
+            .map(|value| value.to_string_lossy())
+            .collect::<Vec<_>>();
+        let value = match arg.get_action() {
+            ArgAction::SetTrue => Value::Flag {
+                set: false,
+                default: false,
+            },
+            ArgAction::SetFalse => Value::Flag {
+                set: true,
+                default: true,
+            },
+            ArgAction::Set | ArgAction::Append => {
+                let options = arg
+                    .get_possible_values()
+                    .iter()
+                    .filter(|value| !value.is_hide_set())
+                    .map(|value| value.get_name().to_string())
+                    .collect::<Vec<_>>();
+                if options.is_empty() {
+                    let text = default.join(" ");
+                    Value::Text {
+                        cursor: text.chars().count(),
+                        text,
+                    }
+                } else {
+                    let selected = options
+                        .iter()
+                        .position(|o| default.contains(&o.as_str().into()));
+                    Value::Choice { options, selected }
+                }
+            }
+            _ => return None,
+        };
+
+        let help = arg.get_help().map(ToString::to_string);
+        let label = help.clone().unwrap_or_else(|| {
+            arg.get_long()
+                .map_or_else(|| arg.get_id().to_string(), str::to_string)
+        });
+        let help = arg
+            .get_long_help()
+            .map(ToString::to_string)
+            .or(help)
+            .unwrap_or_default();
+
+        Some(Self {
+            arg: arg.clone(),
+            label,
+            help,
+            value,
+            error: None,
+        })
+    }
+
+    fn is_path(&self) -> bool {
+        matches!(
+            self.arg.get_value_hint(),
+            ValueHint::AnyPath
+                | ValueHint::FilePath
+                | ValueHint::DirPath
+                | ValueHint::ExecutablePath
+        )
+    }
+
+    fn is_multiple(&self) -> bool {
+        matches!(self.arg.get_action(), ArgAction::Append)
+            || self
+                .arg
+                .get_num_args()
+                .is_some_and(|range| range.max_values() > 1)
+    }
+
+    /// Values written or chosen, without the name of the argument
+    fn values(&self) -> Vec<OsString> {
+        match &self.value {
+            Value::Flag { .. } => Vec::new(),
+            Value::Choice { options, selected } => selected
+                .and_then(|i| options.get(i))
+                .map(OsString::from)
+                .into_iter()
+                .collect(),
+            Value::Text { text, .. } => {
+                let words = if self.is_multiple() {
+                    split_words(text)
+                } else if text.trim().is_empty() {
+                    Vec::new()
+                } else {
+                    vec![text.trim()]
+                };
+
+                words
+                    .into_iter()
+                    .map(|word| {
+                        if self.is_path() {
+                            parse_path(word).into_os_string()
+                        } else {
+                            unquote(word).into()
+                        }
+                    })
+                    .collect()
+            }
+        }
+    }
+
+    /// The argument as written in the command line, nothing when it is empty
+    fn args(&self) -> Vec<OsString> {
+        let name = self
+            .arg
+            .get_long()
+            .map(|long| format!("--{long}"))
+            .or_else(|| self.arg.get_short().map(|short| format!("-{short}")))
+            .unwrap_or_default();
+
+        if let Value::Flag { set, default } = self.value {
+            return if set == default {
+                Vec::new()
+            } else {
+                vec![name.into()]
+            };
+        }
+
+        let values = self.values();
+        if matches!(self.arg.get_action(), ArgAction::Append) {
+            values
+                .into_iter()
+                .flat_map(|value| [OsString::from(&name), value])
+                .collect()
+        } else if values.is_empty() {
+            values
+        } else {
+            std::iter::once(name.into()).chain(values).collect()
+        }
+    }
+
+    /// Parse the value alone, returning why it isn't valid
+    fn check(&self) -> Result<(), String> {
+        if self.values().is_empty() {
+            return Ok(());
+        }
+
+        let mut arg = self.arg.clone().required(false);
+        let mut args = self.args();
+        if arg.is_positional() {
+            arg = arg.index(1);
+            args = self.values();
+        }
+
+        Command::new("form")
+            .no_binary_name(true)
+            .disable_help_flag(true)
+            .arg(arg)
+            .try_get_matches_from(args)
+            .map(drop)
+            .map_err(|err| {
+                // The reason of the parser is enough next to the field
+                std::error::Error::source(&err)
+                    .map_or_else(|| first_line(&err), ToString::to_string)
+            })
+    }
+
+    /// Text shown for the value in `width` columns and where the cursor goes
+    fn show(&self, width: usize) -> (String, Option<usize>) {
+        match &self.value {
+            Value::Flag { set, .. } => (if *set { "[x]" } else { "[ ]" }.to_string(), None),
+            Value::Choice { options, selected } => {
+                let option = selected
+                    .and_then(|i| options.get(i))
+                    .map_or("—", String::as_str);
+                (format!("< {option} >"), None)
+            }
+            Value::Text { text, cursor } => {
+                // Scroll so the cursor is always visible
+                let start = (cursor + 1).saturating_sub(width);
+                let shown = text.chars().skip(start).take(width).collect::<String>();
+                let padding = width.saturating_sub(shown.chars().count()).min(1);
+                (
+                    format!("{shown}{}", " ".repeat(padding)),
+                    Some(cursor - start),
+                )
+            }
+        }
+    }
+
+    fn key(&mut self, key: KeyEvent) {
+        match &mut self.value {
+            Value::Flag { set, .. } => {
+                if matches!(
+                    key.code,
+                    KeyCode::Char(' ') | KeyCode::Left | KeyCode::Right
+                ) {
+                    *set = !*set;
+                }
+            }
+            Value::Choice { options, selected } => {
+                let len = options.len();
+                *selected = match (key.code, *selected) {
+                    (KeyCode::Right | KeyCode::Char(' '), None) => Some(0),
+                    (KeyCode::Right | KeyCode::Char(' '), Some(i)) => Some((i + 1) % len),
+                    (KeyCode::Left, None) => Some(len - 1),
+                    (KeyCode::Left, Some(i)) => Some((i + len - 1) % len),
+                    (KeyCode::Backspace | KeyCode::Delete, _) => None,
+                    (_, selected) => selected,
+                };
+            }
+            Value::Text { text, cursor } => edit(text, cursor, key),
+        }
+    }
+
+    /// Complete the path before the cursor, returning the candidates when there are many
+    fn complete(&mut self) -> Vec<String> {
+        let Value::Text { text, cursor } = &mut self.value else {
+            return Vec::new();
+        };
+
+        let pos = byte_index(text, *cursor);
+        let Ok((start, pairs)) = FilenameCompleter::new().complete_path(text, pos) else {
+            return Vec::new();
+        };
+        let replacements = pairs
+            .iter()
+            .map(|pair| pair.replacement.as_str())
+            .collect::<Vec<_>>();
+        let Some(common) = common_prefix(&replacements) else {
+            return Vec::new();
+        };
+
+        if common.len() >= pos - start {
+            text.replace_range(start..pos, common);
+            *cursor = text[..start + common.len()].chars().count();
+        }
+
+        if pairs.len() > 1 {
+            pairs.into_iter().map(|pair| pair.display).collect()
+        } else {
+            Vec::new()
+        }
+    }
+}
+
+/// Edit `text` with a key, `cursor` counts chars
+fn edit(text: &mut String, cursor: &mut usize, key: KeyEvent) {
+    let len = text.chars().count();
+    match key.code {
+        KeyCode::Left => *cursor = cursor.saturating_sub(1),
+        KeyCode::Right => *cursor = (*cursor + 1).min(len),
+        KeyCode::Home => *cursor = 0,
+        KeyCode::End => *cursor = len,
+        KeyCode::Backspace if *cursor > 0 => {
+            *cursor -= 1;
+            text.remove(byte_index(text, *cursor));
+        }
+        KeyCode::Delete if *cursor < len => {
+            text.remove(byte_index(text, *cursor));
+        }
+        KeyCode::Char('u') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+            text.clear();
+            *cursor = 0;
+        }
+        KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+            text.insert(byte_index(text, *cursor), c);
+            *cursor += 1;
+        }
+        _ => (),
+    }
+}
+
+fn byte_index(text: &str, chars: usize) -> usize {
+    text.char_indices()
+        .nth(chars)
+        .map_or(text.len(), |(i, _)| i)
+}
+
+fn common_prefix<'a>(words: &[&'a str]) -> Option<&'a str> {
+    let (first, rest) = words.split_first()?;
+    let len = rest.iter().fold(first.len(), |len, word| {
+        first[..len]
+            .char_indices()
+            .zip(word.chars())
+            .find(|((_, a), b)| a != b)
+            .map_or(len.min(word.len()), |((i, _), _)| i)
+    });
+
+    Some(&first[..len])
+}
+
+/// Split at the spaces outside quotes and not escaped with `\` on Unix, keeping the quotes and
+/// escapes of each word
+fn split_words(text: &str) -> Vec<&str> {
+    let mut words = Vec::new();
+    let mut start = None;
+    let mut quote = None;
+    let mut escaped = false;
+
+    for (i, c) in text.char_indices() {
+        match (c, quote) {
+            _ if escaped => escaped = false,
+            ('\\', _) if cfg!(unix) => escaped = true,
+            (c, Some(q)) if c == q => quote = None,
+            ('"' | '\'', None) => quote = Some(c),
+            (c, None) if c.is_whitespace() => {
+                if let Some(s) = start.take() {
+                    words.push(&text[s..i]);
+                }
+                continue;
+            }
+            _ => (),
+        }
+        start.get_or_insert(i);
+    }
+    if let Some(s) = start {
+        words.push(&text[s..]);
+    }
+
+    words
+}
+
+fn unquote(word: &str) -> &str {
+    ['"', '\'']
+        .into_iter()
+        .find_map(|q| word.strip_prefix(q).and_then(|w| w.strip_suffix(q)))
+        .unwrap_or(word)
+}
+
+/// The message of a clap error, without the usage and the hint to see the help
+fn first_line(err: &clap::Error) -> String {
+    let text = err.to_string();
+    let line = text.lines().next().unwrap_or_default();
+    line.strip_prefix("error: ").unwrap_or(line).to_string()
+}
+
+/// The terminal in raw mode with the form, restored when dropped
+struct Screen(Terminal<CrosstermBackend<Stderr>>);
+
+impl Screen {
+    fn new() -> io::Result<Self> {
+        enable_raw_mode()?;
+        let terminal = match Terminal::new(CrosstermBackend::new(io::stderr())) {
+            Ok(terminal) => terminal,
+            Err(err) => {
+                let _ = disable_raw_mode();
+                return Err(err);
+            }
+        };
+        let screen = Self(terminal);
+        execute!(io::stderr(), EnterAlternateScreen)?;
+
+        Ok(screen)
+    }
+}
+
+impl Drop for Screen {
+    fn drop(&mut self) {
+        let _ = disable_raw_mode();
+        let _ = execute!(io::stderr(), LeaveAlternateScreen);
+        let _ = self.0.show_cursor();
+    }
+}
+
+/// Whether the tool was started without arguments in a terminal where a [`Form`] can be shown
+#[must_use]
+pub fn wants_form() -> bool {
+    std::env::args_os().len() <= 1 && crate::prompter().supports_form()
+}
+
+/// Ask every argument of `T` in a [`Form`], except the ones in `skip`. Returns None if the form
+/// was cancelled or couldn't be shown
+#[must_use]
+pub fn ask_args<T: CommandFactory + FromArgMatches>(skip: &[&str]) -> Option<T> {
+    Form::new(T::command())
+        .skip(skip)
+        .ask()
+        .unwrap_or_else(|err| {
+            eprintln!("{}", msg::NO_FORM.fill(err));
+            None
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+    use std::path::PathBuf;
+
+    #[derive(Parser, Debug, PartialEq)]
+    #[clap(name = "concentra")]
+    struct Args {
+        /// Archivos a concentrar
+        rutas: Vec<PathBuf>,
+        /// Directorio de salida
+        #[clap(long, short)]
+        salida: Option<PathBuf>,
+        /// Número de campus
+        #[clap(long, default_value = "3")]
+        numero_campus: usize,
+        /// Escribir descartados
+        #[clap(long)]
+        descartados: bool,
+        #[clap(long)]
+        cli: bool,
+    }
+
+    fn write(form: &mut Form, id: &str, value: &str) {
+        let field = form
+            .fields
+            .iter_mut()
+            .find(|field| field.arg.get_id() == id)
+            .expect("field");
+        match &mut field.value {
+            Value::Flag { set, .. } => *set = value == "true",
+            Value::Text { text, cursor } => {
+                *text = value.to_string();
+                *cursor = text.chars().count();
+            }
+            Value::Choice { .. } => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn fields() {
+        let mut form = Form::new(Args::command()).skip(&["cli"]);
+        let labels = form
+            .fields
+            .iter()
+            .map(|f| f.label.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            labels,
+            [
+                "Archivos a concentrar",
+                "Directorio de salida",
+                "Número de campus",
+                "Escribir descartados"
+            ]
+        );
+        assert!(form.fields[0].is_path() && form.fields[0].is_multiple());
+
+        write(&mut form, "rutas", r#"a.xlsx "con espacio.xlsx""#);
+        write(&mut form, "descartados", "true");
+        assert_eq!(
+            form.args(),
+            [
+                "concentra",
+                "--numero-campus",
+                "3",
+                "--descartados",
+                "--",
+                "a.xlsx",
+                "con espacio.xlsx"
+            ]
+        );
+
+        let matches = form.check().expect("valid");
+        let args = Args::from_arg_matches(&matches).expect("parsed");
+        assert_eq!(
+            args.rutas,
+            [PathBuf::from("a.xlsx"), "con espacio.xlsx".into()]
+        );
+        assert_eq!(args.numero_campus, 3);
+
+        write(&mut form, "numero_campus", "tres");
+        assert!(form.check().is_none());
+        assert!(form.fields[2].error.is_some());
+        assert!(form.fields[0].error.is_none());
+    }
+
+    #[test]
+    fn words() {
+        assert_eq!(split_words(" a  'b c' d"), ["a", "'b c'", "d"]);
+        assert_eq!(common_prefix(&["datos/a", "datos/b"]), Some("datos/"));
+        assert_eq!(common_prefix(&[]), None);
+
+        let mut text = "ab".to_string();
+        let mut cursor = 1;
+        edit(&mut text, &mut cursor, KeyCode::Char('ñ').into());
+        edit(&mut text, &mut cursor, KeyCode::Right.into());
+        edit(&mut text, &mut cursor, KeyCode::Backspace.into());
+        assert_eq!((text.as_str(), cursor), ("añ", 2));
+    }
+}
@@ -0,0 +1,129 @@
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    io,
+    path::{Path, PathBuf},
+};
+
+use crate::PathKind;
+
+/// Last choices of a question
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct Entry {
+    pub directory: Option<PathBuf>,
+    pub paths: Vec<PathBuf>,
+}
+
+impl Entry {
+    /// Directory to start in, if it still exists
+    pub fn directory(&self) -> Option<&Path> {
+        self.directory.as_deref().filter(|dir| dir.is_dir())
+    }
+}
+
+/// Last choices of every question of a tool, keyed by the [`Title`](crate::Title) key of the question
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub(crate) struct History {
+    entries: HashMap<String, Entry>,
+}
+
+impl History {
+    /// History of the running tool, empty if it doesn't exist or can't be read
+    pub fn load() -> Self {
+        history_path()
+            .and_then(|path| std::fs::read_to_string(path).ok())
+            .and_then(|text| serde_json::from_str(&text).ok())
+            .unwrap_or_default()
+    }
+
+    /// Write the history of the running tool
+    pub fn save(&self) -> io::Result<()> {
+        let Some(path) = history_path() else {
+            return Ok(());
+        };
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        std::fs::write(path, serde_json::to_string_pretty(self)?)
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Entry> {
+        self.entries.get(key)
+    }
+
+    /// Remember `paths` as the choice of the question `key`. The directory is the chosen folder
+    /// or the folder of the chosen file
+    pub fn remember(&mut self, key: &str, kind: PathKind, paths: &[PathBuf]) {
+        let directory = paths.first().and_then(|path| {
+            if kind.is_folder() {
+                Some(path.clone())
+            } else {
+                path.parent()
+                    .filter(|p| !p.as_os_str().is_empty())
+                    .map(Path::to_path_buf)
+            }
+        });
+        let paths = paths
+            .iter()
+            .map(|path| std::path::absolute(path).unwrap_or_else(|_| path.clone()))
+            .collect();
+        let directory = directory.map(|dir| std::path::absolute(&dir).unwrap_or(dir));
+
+        self.entries
+            .insert(key.to_string(), Entry { directory, paths });
+    }
+}
+
+/// Per user file with the history of the running tool, named after its executable
+fn history_path() -> Option<PathBuf> {
+    let exe = std::env::current_exe().ok()?;
+    let tool = exe.file_stem()?.to_string_lossy();
+
+    Some(
+        dirs::config_dir()?
+            .join("dialogs")
+            .join(format!("{tool}.json")),
+    )
+}
+
+/// Forget the last directories and choices of every question of the running tool
+///
+/// # Errors
+///
+/// If the history exists but can't be removed
+pub fn clear_history() -> io::Result<()> {
+    match history_path().map(std::fs::remove_file) {
+        Some(Err(err)) if err.kind() != io::ErrorKind::NotFound => Err(err),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn remember() {
+        let dir = std::env::temp_dir();
+        let mut history = History::default();
+
+        history.remember("Archivos", PathKind::OpenFiles, &[dir.join("a.xlsx")]);
+        history.remember("Carpeta", PathKind::OpenFolder, std::slice::from_ref(&dir));
+
+        let files = history.get("Archivos").expect("remembered");
+        assert_eq!(files.directory(), Some(dir.as_path()));
+        assert_eq!(files.paths, [dir.join("a.xlsx")]);
+        assert_eq!(
+            history.get("Carpeta").and_then(Entry::directory),
+            Some(dir.as_path())
+        );
+
+        let json = serde_json::to_string(&history).expect("serializable");
+        assert_eq!(
+            serde_json::from_str::<History>(&json).expect("valid JSON"),
+            history
+        );
+    }
+}
@@ -0,0 +1,304 @@
+use std::{
+    fmt,
+    str::FromStr,
+    sync::atomic::{AtomicU8, Ordering},
+};
+use thiserror::Error;
+
+/// Environment variable with the language of the messages, `es` or `en`
+pub const LANG_VAR: &str = "DIALOGS_LANG";
+/// Variables of the system locale in the order they take precedence
+const LOCALE_VARS: &[&str] = &["LC_ALL", "LC_MESSAGES", "LANG"];
+
+/// Language of the messages
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Lang {
+    #[default]
+    Es,
+    En,
+}
+
+impl Lang {
+    /// Language of a locale like `es-MX` or `en_US.UTF-8`: Spanish for `es` and English for any
+    /// other. None for the `C` and `POSIX` locales, that don't choose a language
+    #[must_use]
+    pub fn from_locale(locale: &str) -> Option<Self> {
+        let locale = locale.trim();
+        if locale.is_empty() || locale == "C" || locale.starts_with("C.") || locale == "POSIX" {
+            None
+        } else if locale
+            .get(..2)
+            .is_some_and(|l| l.eq_ignore_ascii_case("es"))
+        {
+            Some(Self::Es)
+        } else {
+            Some(Self::En)
+        }
+    }
+
+    /// Language of the system locale: the first of `LC_ALL`, `LC_MESSAGES` and `LANG` that is set,
+    /// or the locale of the user when there is none, like in Windows
+    fn from_system() -> Option<Self> {
+        let var = LOCALE_VARS
+            .iter()
+            .find_map(|var| std::env::var(var).ok().filter(|v| !v.is_empty()));
+
+        match var {
+            Some(locale) => Self::from_locale(&locale),
+            None => sys_locale::get_locale().and_then(|locale| Self::from_locale(&locale)),
+        }
+    }
+}
+
+/// A language that isn't `es` nor `en`
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[error("{}", msg::UNKNOWN_LANG.fill(.0))]
+pub struct UnknownLang(pub String);
+
+impl FromStr for Lang {
+    type Err = UnknownLang;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "es" | "español" | "espanol" | "spanish" => Ok(Self::Es),
+            "en" | "inglés" | "ingles" | "english" => Ok(Self::En),
+            other => Err(UnknownLang(other.to_string())),
+        }
+    }
+}
+
+impl fmt::Display for Lang {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Es => "es",
+            Self::En => "en",
+        })
+    }
+}
+
+/// 0 until the language is set or detected
+static LANG: AtomicU8 = AtomicU8::new(0);
+
+/// Language of the messages: the one of [`set_lang`], else [`LANG_VAR`], else the one of the
+/// system locale and Spanish if there is none
+pub fn lang() -> Lang {
+    match LANG.load(Ordering::Relaxed) {
+        1 => Lang::Es,
+        2 => Lang::En,
+        _ => {
+            let lang = std::env::var(LANG_VAR)
+                .ok()
+                .and_then(|lang| lang.parse().ok())
+                .or_else(Lang::from_system)
+                .unwrap_or_default();
+            set_lang(lang);
+            lang
+        }
+    }
+}
+
+/// Use `lang` for every message, like when it is chosen with a flag
+pub fn set_lang(lang: Lang) {
+    let value = match lang {
+        Lang::Es => 1,
+        Lang::En => 2,
+    };
+    LANG.store(value, Ordering::Relaxed);
+}
+
+/// A message in every language
+///
+/// ```
+/// use dialogs::{set_lang, Lang, Text};
+///
+/// const SAVED: Text = Text::new("Guardado en {}", "Saved in {}");
+///
+/// set_lang(Lang::En);
+/// assert_eq!(SAVED.fill("a.xlsx"), "Saved in a.xlsx");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Text {
+    pub es: &'static str,
+    pub en: &'static str,
+}
+
+impl Text {
+    #[must_use]
+    pub const fn new(es: &'static str, en: &'static str) -> Self {
+        Self { es, en }
+    }
+
+    /// The message in the current [`lang`]
+    #[must_use]
+    pub fn get(self) -> &'static str {
+        self.get_in(lang())
+    }
+
+    #[must_use]
+    pub const fn get_in(self, lang: Lang) -> &'static str {
+        match lang {
+            Lang::Es => self.es,
+            Lang::En => self.en,
+        }
+    }
+
+    /// The message in the current [`lang`] with its `{}` replaced by `arg`
+    #[must_use]
+    pub fn fill(self, arg: impl fmt::Display) -> String {
+        self.get().replacen("{}", &arg.to_string(), 1)
+    }
+}
+
+impl fmt::Display for Text {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.get())
+    }
+}
+
+/// Title of a question and the key that names it in the history and in the answers files, so
+/// they work in every language. The key of a [`Text`] is its Spanish message, the key of a plain
+/// `&str` is the text itself
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Title<'a> {
+    /// What the user sees
+    pub text: &'a str,
+    pub key: &'a str,
+}
+
+impl From<Text> for Title<'static> {
+    fn from(text: Text) -> Self {
+        Self {
+            text: text.get(),
+            key: text.es,
+        }
+    }
+}
+
+impl<'a> From<&'a str> for Title<'a> {
+    fn from(text: &'a str) -> Self {
+        Self { text, key: text }
+    }
+}
+
+/// Messages of the dialogs, and common ones for the tools
+pub mod msg {
+    use super::Text;
+
+    pub const CANCEL_TITLE: Text = Text::new("Cancelar y salir", "Cancel and exit");
+    pub const CANCEL_DESCRIPTION: Text = Text::new(
+        "¿Seguro que quieres cancelar y terminar el programa?",
+        "Are you sure you want to cancel and exit the program?",
+    );
+    pub const PATH_PROMPT: Text =
+        Text::new("Ruta (vacía para cancelar): ", "Path (empty to cancel): ");
+    pub const PATHS_PROMPT: Text = Text::new(
+        "Ruta {} (vacía para terminar): ",
+        "Path {} (empty to finish): ",
+    );
+    pub const YES_NO: Text = Text::new("[s/n]", "[y/n]");
+    pub const ANSWER_YES_NO: Text = Text::new("Responde s o n", "Answer y or n");
+    pub const REPLACE_TITLE: Text = Text::new("Reemplazar", "Replace");
+    pub const REPLACE_DESCRIPTION: Text = Text::new("¿Reemplazar {}?", "Replace {}?");
+    pub const NO_FOLDER: Text = Text::new("No existe la carpeta {}", "The folder {} doesn't exist");
+    pub const NO_FILE: Text = Text::new("No existe el archivo {}", "The file {} doesn't exist");
+    pub const IS_FOLDER: Text = Text::new("{} es una carpeta", "{} is a folder");
+    pub const BAD_EXTENSION: Text = Text::new(
+        "{} no tiene una extensión válida",
+        "{} doesn't have a valid extension",
+    );
+    pub const INVALID_TITLE: Text = Text::new("Selección no válida", "Invalid selection");
+    pub const WARNING: Text = Text::new("Advertencia", "Warning");
+    pub const ERROR: Text = Text::new("Error", "Error");
+    pub const DETAILS: Text = Text::new("Detalles", "Details");
+    pub const DETAILS_IN: Text = Text::new("Los detalles están en {}", "The details are in {}");
+    pub const COPY_DETAILS: Text = Text::new("Copiar detalles", "Copy details");
+    pub const CLOSE: Text = Text::new("Cerrar", "Close");
+    pub const UNEXPECTED: Text = Text::new(
+        "Ocurrió un error inesperado y el programa terminará",
+        "An unexpected error happened and the program will end",
+    );
+    pub const NO_PANIC_MESSAGE: Text = Text::new("sin mensaje", "no message");
+    pub const THREAD: Text = Text::new("Hilo", "Thread");
+    pub const ARGUMENTS: Text = Text::new("Argumentos", "Arguments");
+    pub const LOG: Text = Text::new("Registro", "Log");
+    pub const NO_PROGRESS_WINDOW: Text = Text::new(
+        "No se pudo mostrar el progreso: {}",
+        "The progress couldn't be shown: {}",
+    );
+    pub const CANCEL: Text = Text::new("Cancelar", "Cancel");
+    pub const CANCELLING: Text = Text::new("Cancelando…", "Cancelling…");
+    pub const CANCELLED: Text = Text::new("Cancelado", "Cancelled");
+    pub const DONE: Text = Text::new("Listo", "Done");
+    pub const RUN: Text = Text::new("Ejecutar", "Run");
+    pub const FORM_KEYS: Text = Text::new(
+        "↑↓ cambiar de campo · Enter siguiente · Tab completar ruta · Espacio marcar · Esc cancelar",
+        "↑↓ change field · Enter next · Tab complete path · Space check · Esc cancel",
+    );
+    pub const FORM_INVALID: Text = Text::new("Corrige los campos en rojo", "Fix the fields in red");
+    pub const NO_FORM: Text = Text::new(
+        "No se pudo mostrar el formulario: {}",
+        "The form couldn't be shown: {}",
+    );
+    pub const HISTORY_NOT_CLEARED: Text = Text::new(
+        "No se pudo borrar el historial de los diálogos: {}",
+        "The history of the dialogs couldn't be cleared: {}",
+    );
+    pub const UNKNOWN_LANG: Text = Text::new(
+        "idioma desconocido `{}`, usa `es` o `en`",
+        "unknown language `{}`, use `es` or `en`",
+    );
+    pub const ANSWERS_NOT_READ: Text = Text::new(
+        "no se pudo leer el archivo de respuestas {}",
+        "the answers file {} couldn't be read",
+    );
+    pub const INVALID_JSON_ANSWERS: Text =
+        Text::new("respuestas JSON inválidas: {}", "invalid JSON answers: {}");
+    pub const INVALID_TOML_ANSWERS: Text =
+        Text::new("respuestas TOML inválidas: {}", "invalid TOML answers: {}");
+    pub const NO_ANSWER: Text = Text::new(
+        "no hay respuesta para el diálogo «{}»",
+        "there is no answer for the dialog «{}»",
+    );
+    pub const ANSWER_NOT_KIND: Text = Text::new(
+        "la respuesta del diálogo «{}» no es",
+        "the answer of the dialog «{}» isn't",
+    );
+    pub const ANSWER_INVALID: Text = Text::new(
+        "la respuesta del diálogo «{}» no es válida",
+        "the answer of the dialog «{}» isn't valid",
+    );
+    pub const KIND_YES_NO: Text = Text::new("sí o no", "yes or no");
+    pub const KIND_PATH: Text = Text::new("una ruta", "a path");
+    pub const KIND_PATHS: Text = Text::new("una lista de rutas", "a list of paths");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn langs() {
+        assert_eq!("Español".parse(), Ok(Lang::Es));
+        assert!("fr".parse::<Lang>().is_err());
+        assert_eq!(
+            "fr".parse::<Lang>().map_err(|err| err.to_string()),
+            Err(msg::UNKNOWN_LANG.fill("fr"))
+        );
+
+        assert_eq!(Lang::from_locale("es_MX.UTF-8"), Some(Lang::Es));
+        assert_eq!(Lang::from_locale("en-US"), Some(Lang::En));
+        assert_eq!(Lang::from_locale("C.UTF-8"), None);
+
+        assert_eq!(msg::NO_FILE.get_in(Lang::En), "The file {} doesn't exist");
+    }
+
+    #[test]
+    fn titles() {
+        // The key doesn't change with the language
+        let title = Title::from(msg::CANCEL_TITLE);
+        assert_eq!(title.text, msg::CANCEL_TITLE.get());
+        assert_eq!(title.key, "Cancelar y salir");
+
+        assert_eq!(Title::from("Salida").key, "Salida");
+    }
+}
@@ -5,69 +5,293 @@
 #![deny(clippy::missing_panics_doc)]
 #![deny(clippy::unwrap_used)]
 
-use rfd::{FileDialog, MessageDialog, MessageDialogResult};
-use std::path::PathBuf;
+//! Ask the user for paths in a loop, confirming before cancelling
+//!
+//! The `_with` variants of the questions take a validator and ask again, telling the user why,
+//! until every chosen path is valid or the question is cancelled.
+//!
+//! The questions are shown with native dialogs when there is a display and in the terminal when
+//! there is not, like over SSH. The backend can be forced with the [`BACKEND_VAR`] environment
+//! variable set to `native` or `terminal`, or replaced with [`set_prompter`]. If [`ANSWERS_VAR`]
+//! is set, the answers are read from it instead of asking, see [`ScriptedPrompter`].
+//!
+//! The last directory and choices of each question are remembered per tool and question in the
+//! user config directory, to start there the next time. [`clear_history`] forgets them. Questions
+//! are named by the key of their [`Title`], so the history and the answers files work in every
+//! language.
+//!
+//! Long jobs report their progress with [`with_progress`], in a window or with bars in the
+//! terminal. Errors are shown with [`report_error`] and written to a log, [`install_panic_hook`]
+//! does the same with panics for tools without a console.
+//!
+//! Tools started without arguments in a terminal can ask all of them in one [`Form`] made from
+//! their clap arguments, see [`wants_form`].
+//!
+//! Every message is in Spanish or English, as chosen or from the system locale, see [`lang`].
+//! Tools use [`Text`] for theirs.
 
-#[derive(Debug, Clone, Copy)]
-enum OpKind {
+mod form;
+mod history;
+mod i18n;
+mod native;
+mod progress;
+mod report;
+mod scripted;
+mod terminal;
+mod window;
+
+pub use form::{ask_args, wants_form, Form};
+pub use history::clear_history;
+pub use i18n::{lang, msg, set_lang, Lang, Text, Title, UnknownLang, LANG_VAR};
+pub use native::NativePrompter;
+pub use progress::{with_progress, Progress, ProgressMode, Task, TaskState};
+pub use report::{install_panic_hook, log, report_error, set_log_dir};
+pub use scripted::{Answer, AnswersError, ScriptedPrompter, ANSWERS_VAR};
+pub use terminal::TerminalPrompter;
+
+use history::History;
+use std::{
+    path::{Path, PathBuf},
+    sync::OnceLock,
+};
+
+/// Environment variable that forces the backend, `native` or `terminal`
+pub const BACKEND_VAR: &str = "DIALOGS_BACKEND";
+
+/// What is being asked for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PathKind {
     OpenFile,
+    OpenFiles,
     OpenFolder,
+    OpenFolders,
     SaveFile,
 }
 
+impl PathKind {
+    /// Whether more than one path may be chosen
+    #[must_use]
+    pub const fn is_multiple(self) -> bool {
+        matches!(self, Self::OpenFiles | Self::OpenFolders)
+    }
+
+    /// Whether the paths are folders instead of files
+    #[must_use]
+    pub const fn is_folder(self) -> bool {
+        matches!(self, Self::OpenFolder | Self::OpenFolders)
+    }
+}
+
+/// Importance of a message
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Level {
+    Info,
+    Warning,
+    Error,
+}
+
+/// A question for one or more paths
 #[derive(Debug, Clone, Copy)]
-enum MultiOpKind {
-    OpenFiles,
-    OpenFolders,
+pub struct PathRequest<'a> {
+    pub kind: PathKind,
+    pub title: &'a str,
+    /// Name of the question in the history and the answers files, see [`Title`]
+    pub key: &'a str,
+    /// Pairs of (name, extensions) of the files that can be chosen
+    pub filters: Option<&'a [(&'a str, &'a [&'a str])]>,
+    /// Directory to start in
+    pub directory: Option<&'a Path>,
+    /// Paths chosen the last time, to pre-select them. Native dialogs can only pre-select one
+    /// file, so they do it for [`PathKind::OpenFile`] and [`PathKind::SaveFile`] and the other kinds
+    /// only start in the directory; in the terminal every path is offered as the initial answer
+    pub last: &'a [PathBuf],
+}
+
+impl<'a> PathRequest<'a> {
+    /// Request without directory nor last paths
+    #[must_use]
+    pub fn new(
+        kind: PathKind,
+        title: impl Into<Title<'a>>,
+        filters: Option<&'a [(&'a str, &'a [&'a str])]>,
+    ) -> Self {
+        let Title { text, key } = title.into();
+        Self {
+            kind,
+            title: text,
+            key,
+            filters,
+            directory: None,
+            last: &[],
+        }
+    }
+}
+
+/// A way to ask the user questions
+pub trait Prompter: Send + Sync {
+    /// Ask once for the paths of `request`. Returns None if the user closed the question without
+    /// choosing, single kinds return one path
+    fn ask_paths(&self, request: &PathRequest<'_>) -> Option<Vec<PathBuf>>;
+
+    /// Ask a yes or no question. Returns None if it can't be answered, like when stdin is closed
+    fn confirm(&self, title: Title<'_>, description: &str) -> Option<bool>;
+
+    /// Show a message that only needs to be acknowledged
+    fn message(&self, level: Level, title: &str, description: &str);
+
+    /// Tell the user why the paths chosen for the question `title` aren't valid, before asking
+    /// again
+    fn reject(&self, _title: &str, reason: &str) {
+        self.message(Level::Warning, msg::INVALID_TITLE.get(), reason);
+    }
+
+    /// Show an error with details the user may want to read and the log where it was written
+    fn error(&self, message: &str, details: &str, log: Option<&Path>) {
+        eprintln!("{}: {message}\n{details}", msg::ERROR);
+        if let Some(log) = log {
+            eprintln!("{}", msg::DETAILS_IN.fill(log.display()));
+        }
+    }
+
+    /// Whether the choices are remembered to pre-select them the next time
+    fn keeps_history(&self) -> bool {
+        true
+    }
+
+    /// Whether the questions are shown in native windows
+    fn is_native(&self) -> bool {
+        false
+    }
+
+    /// Whether a [`Form`] can be shown instead of asking one question after another
+    fn supports_form(&self) -> bool {
+        false
+    }
+}
+
+static PROMPTER: OnceLock<Box<dyn Prompter>> = OnceLock::new();
+
+/// Prompter used by every question, chosen on the first call
+pub fn prompter() -> &'static dyn Prompter {
+    PROMPTER.get_or_init(default_prompter).as_ref()
+}
+
+/// Use `prompter` for every question instead of the automatic choice
+///
+/// # Errors
+///
+/// Returns the prompter back if a question was already asked or a prompter was already set
+pub fn set_prompter(prompter: impl Prompter + 'static) -> Result<(), Box<dyn Prompter>> {
+    PROMPTER.set(Box::new(prompter))
+}
+
+/// Whether native dialogs can be shown. Always true on Windows and macOS, on other systems there
+/// must be an X11 or Wayland display
+#[must_use]
+pub fn has_display() -> bool {
+    if cfg!(any(target_os = "windows", target_os = "macos")) {
+        return true;
+    }
+
+    ["DISPLAY", "WAYLAND_DISPLAY"]
+        .into_iter()
+        .any(|var| std::env::var_os(var).is_some_and(|value| !value.is_empty()))
 }
 
+fn default_prompter() -> Box<dyn Prompter> {
+    if let Some(answers) = ScriptedPrompter::from_env() {
+        return Box::new(answers.unwrap_or_else(|err| scripted::fail(&err)));
+    }
+
+    match std::env::var(BACKEND_VAR).as_deref() {
+        Ok("native") => Box::new(NativePrompter),
+        Ok("terminal") => Box::new(TerminalPrompter),
+        _ if has_display() => Box::new(NativePrompter),
+        _ => Box::new(TerminalPrompter),
+    }
+}
+
+/// Checks a chosen path, returning why it isn't valid
+pub type Validator<'a> = &'a dyn Fn(&Path) -> Result<(), String>;
+
 fn ask_multiple_op(
-    kind: MultiOpKind,
-    title: &str,
+    kind: PathKind,
+    title: Title<'_>,
     filters: Option<&[(&str, &[&str])]>,
+    validate: Validator<'_>,
 ) -> Option<Vec<PathBuf>> {
-    loop {
-        let dialog = append_filters(filters, FileDialog::new()).set_title(title);
-        let res = match kind {
-            MultiOpKind::OpenFiles => dialog.pick_files(),
-            MultiOpKind::OpenFolders => dialog.pick_folders(),
-        };
+    let request = PathRequest::new(kind, title, filters);
+    ask_until_valid(prompter(), &request, validate, true)
+}
+
+/// Ask until `validate` accepts the paths. If `confirm` closing the question asks whether to
+/// cancel and asks again if the user doesn't want to, otherwise it returns None right away
+fn ask_until_valid(
+    prompter: &dyn Prompter,
+    request: &PathRequest<'_>,
+    validate: Validator<'_>,
+    confirm: bool,
+) -> Option<Vec<PathBuf>> {
+    let PathRequest { kind, key, .. } = *request;
+    let keeps_history = prompter.keeps_history();
+    let mut history = if keeps_history {
+        History::load()
+    } else {
+        History::default()
+    };
+
+    let entry = history.get(key).cloned().unwrap_or_default();
+    let last = entry
+        .paths
+        .iter()
+        .filter(|path| kind == PathKind::SaveFile || path.exists())
+        .cloned()
+        .collect::<Vec<_>>();
+    let request = PathRequest {
+        directory: request.directory.or_else(|| entry.directory()),
+        last: &last,
+        ..*request
+    };
 
-        let Some(file) = res else {
-            if let MessageDialogResult::Yes = confirm_cancel() {
+    loop {
+        let Some(paths) = prompter.ask_paths(&request) else {
+            if !confirm || confirm_cancel(prompter) {
                 break None;
             }
 
             continue;
         };
 
-        break Some(file);
+        let errors = paths
+            .iter()
+            .filter_map(|path| {
+                validate(path)
+                    .err()
+                    .map(|err| format!("{}: {err}", path.display()))
+            })
+            .collect::<Vec<_>>();
+        if !errors.is_empty() {
+            prompter.reject(request.title, &errors.join("\n"));
+            continue;
+        }
+
+        if keeps_history {
+            history.remember(key, kind, &paths);
+            // The history is only a convenience, failing to write it doesn't stop the program
+            let _ = history.save();
+        }
+
+        break Some(paths);
     }
 }
 
 fn ask_single_op(
-    kind: OpKind,
-    title: &str,
+    kind: PathKind,
+    title: Title<'_>,
     filters: Option<&[(&str, &[&str])]>,
+    validate: Validator<'_>,
 ) -> Option<PathBuf> {
-    loop {
-        let dialog = append_filters(filters, FileDialog::new()).set_title(title);
-        let res = match kind {
-            OpKind::OpenFile => dialog.pick_file(),
-            OpKind::OpenFolder => dialog.pick_folder(),
-            OpKind::SaveFile => dialog.save_file(),
-        };
-
-        let Some(file) = res else {
-            if let MessageDialogResult::Yes = confirm_cancel() {
-                break None;
-            }
-
-            continue;
-        };
-
-        break Some(file);
-    }
+    ask_multiple_op(kind, title, filters, validate).and_then(|paths| paths.into_iter().next())
 }
 
 /// Ask where to save a file. Returns None if the user confirmed he wanted to cancel the selection.
@@ -75,8 +299,21 @@ fn ask_single_op(
 /// After the user asking for the operation to be cancelled the program may be terminated without further
 /// confirmation by the caller
 #[must_use]
-pub fn ask_save_file(title: &str, filters: Option<&[(&str, &[&str])]>) -> Option<PathBuf> {
-    ask_single_op(OpKind::SaveFile, title, filters)
+pub fn ask_save_file<'a>(
+    title: impl Into<Title<'a>>,
+    filters: Option<&[(&str, &[&str])]>,
+) -> Option<PathBuf> {
+    ask_single_op(PathKind::SaveFile, title.into(), filters, &|_| Ok(()))
+}
+
+/// Like [`ask_save_file`], asking again until `validate` accepts chosen path
+#[must_use]
+pub fn ask_save_file_with<'a>(
+    title: impl Into<Title<'a>>,
+    filters: Option<&[(&str, &[&str])]>,
+    validate: impl Fn(&Path) -> Result<(), String>,
+) -> Option<PathBuf> {
+    ask_single_op(PathKind::SaveFile, title.into(), filters, &validate)
 }
 
 /// Ask for a file to open. Returns None if the user confirmed he wanted to cancel the selection.
@@ -84,8 +321,21 @@ pub fn ask_save_file(title: &str, filters: Option<&[(&str, &[&str])]>) -> Option
 /// After the user asking for the operation to be cancelled the program may be terminated without further
 /// confirmation by the caller
 #[must_use]
-pub fn ask_open_file(title: &str, filters: Option<&[(&str, &[&str])]>) -> Option<PathBuf> {
-    ask_single_op(OpKind::OpenFile, title, filters)
+pub fn ask_open_file<'a>(
+    title: impl Into<Title<'a>>,
+    filters: Option<&[(&str, &[&str])]>,
+) -> Option<PathBuf> {
+    ask_single_op(PathKind::OpenFile, title.into(), filters, &|_| Ok(()))
+}
+
+/// Like [`ask_open_file`], asking again until `validate` accepts chosen path
+#[must_use]
+pub fn ask_open_file_with<'a>(
+    title: impl Into<Title<'a>>,
+    filters: Option<&[(&str, &[&str])]>,
+    validate: impl Fn(&Path) -> Result<(), String>,
+) -> Option<PathBuf> {
+    ask_single_op(PathKind::OpenFile, title.into(), filters, &validate)
 }
 
 /// Ask for a folder to open. Returns None if the user confirmed he wanted to cancel the selection.
@@ -93,8 +343,21 @@ pub fn ask_open_file(title: &str, filters: Option<&[(&str, &[&str])]>) -> Option
 /// After the user asking for the operation to be cancelled the program may be terminated without further
 /// confirmation by the caller
 #[must_use]
-pub fn ask_open_folder(title: &str, filters: Option<&[(&str, &[&str])]>) -> Option<PathBuf> {
-    ask_single_op(OpKind::OpenFolder, title, filters)
+pub fn ask_open_folder<'a>(
+    title: impl Into<Title<'a>>,
+    filters: Option<&[(&str, &[&str])]>,
+) -> Option<PathBuf> {
+    ask_single_op(PathKind::OpenFolder, title.into(), filters, &|_| Ok(()))
+}
+
+/// Like [`ask_open_folder`], asking again until `validate` accepts chosen path
+#[must_use]
+pub fn ask_open_folder_with<'a>(
+    title: impl Into<Title<'a>>,
+    filters: Option<&[(&str, &[&str])]>,
+    validate: impl Fn(&Path) -> Result<(), String>,
+) -> Option<PathBuf> {
+    ask_single_op(PathKind::OpenFolder, title.into(), filters, &validate)
 }
 
 /// Ask for multiple files to open. Returns None if the user confirmed he wanted to cancel the selection.
@@ -102,8 +365,21 @@ pub fn ask_open_folder(title: &str, filters: Option<&[(&str, &[&str])]>) -> Opti
 /// After the user asking for the operation to be cancelled the program may be terminated without further
 /// confirmation by the caller
 #[must_use]
-pub fn ask_open_files(title: &str, filters: Option<&[(&str, &[&str])]>) -> Option<Vec<PathBuf>> {
-    ask_multiple_op(MultiOpKind::OpenFiles, title, filters)
+pub fn ask_open_files<'a>(
+    title: impl Into<Title<'a>>,
+    filters: Option<&[(&str, &[&str])]>,
+) -> Option<Vec<PathBuf>> {
+    ask_multiple_op(PathKind::OpenFiles, title.into(), filters, &|_| Ok(()))
+}
+
+/// Like [`ask_open_files`], asking again until `validate` accepts every chosen path
+#[must_use]
+pub fn ask_open_files_with<'a>(
+    title: impl Into<Title<'a>>,
+    filters: Option<&[(&str, &[&str])]>,
+    validate: impl Fn(&Path) -> Result<(), String>,
+) -> Option<Vec<PathBuf>> {
+    ask_multiple_op(PathKind::OpenFiles, title.into(), filters, &validate)
 }
 
 /// Ask for multiple folders to open. Returns None if the user confirmed he wanted to cancel the selection.
@@ -111,25 +387,136 @@ pub fn ask_open_files(title: &str, filters: Option<&[(&str, &[&str])]>) -> Optio
 /// After the user asking for the operation to be cancelled the program may be terminated without further
 /// confirmation by the caller
 #[must_use]
-pub fn ask_open_folders(title: &str, filters: Option<&[(&str, &[&str])]>) -> Option<Vec<PathBuf>> {
-    ask_multiple_op(MultiOpKind::OpenFolders, title, filters)
+pub fn ask_open_folders<'a>(
+    title: impl Into<Title<'a>>,
+    filters: Option<&[(&str, &[&str])]>,
+) -> Option<Vec<PathBuf>> {
+    ask_multiple_op(PathKind::OpenFolders, title.into(), filters, &|_| Ok(()))
+}
+
+/// Like [`ask_open_folders`], asking again until `validate` accepts every chosen path
+#[must_use]
+pub fn ask_open_folders_with<'a>(
+    title: impl Into<Title<'a>>,
+    filters: Option<&[(&str, &[&str])]>,
+    validate: impl Fn(&Path) -> Result<(), String>,
+) -> Option<Vec<PathBuf>> {
+    ask_multiple_op(PathKind::OpenFolders, title.into(), filters, &validate)
+}
+
+/// Ask for the paths of `request` until `validate` accepts them, starting in its directory if it
+/// has one. Unlike the other questions it returns None as soon as the user closes it without
+/// choosing, so the caller decides what comes next, like asking again or [`ask_cancel`]
+#[must_use]
+pub fn ask_paths(
+    request: &PathRequest<'_>,
+    validate: impl Fn(&Path) -> Result<(), String>,
+) -> Option<Vec<PathBuf>> {
+    ask_until_valid(prompter(), request, &validate, false)
+}
+
+/// Ask whether the user wants to cancel and exit the program. An unanswerable question is a yes
+#[must_use]
+pub fn ask_cancel() -> bool {
+    confirm_cancel(prompter())
 }
 
-/// Append a collection of filters with (name, extensions) to a `FileDialog`
-fn append_filters(filters: Option<&[(&str, &[&str])]>, mut dialog: FileDialog) -> FileDialog {
-    if let Some(filters) = filters {
-        for (name, ext) in filters.iter().copied() {
-            dialog = dialog.add_filter(name, ext);
+/// Ask a yes or no question. An unanswerable question is a no
+#[must_use]
+pub fn ask_yes_no<'a>(title: impl Into<Title<'a>>, description: &str) -> bool {
+    prompter()
+        .confirm(title.into(), description)
+        .unwrap_or(false)
+}
+
+/// Show a message that only needs to be acknowledged
+pub fn show_message(level: Level, title: &str, description: &str) {
+    prompter().message(level, title, description);
+}
+
+/// Whether the user wants to cancel. An unanswerable question is a yes, so a closed stdin doesn't
+/// ask forever
+fn confirm_cancel(prompter: &dyn Prompter) -> bool {
+    prompter
+        .confirm(msg::CANCEL_TITLE.into(), msg::CANCEL_DESCRIPTION.get())
+        .unwrap_or(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    /// Answers each question with the next of `answers` and keeps the rejections
+    struct Queue {
+        answers: Mutex<Vec<Option<Vec<PathBuf>>>>,
+        rejected: Mutex<Vec<String>>,
+    }
+
+    impl Prompter for Queue {
+        fn ask_paths(&self, _request: &PathRequest<'_>) -> Option<Vec<PathBuf>> {
+            self.answers.lock().expect("not poisoned").remove(0)
+        }
+
+        fn confirm(&self, _title: Title<'_>, _description: &str) -> Option<bool> {
+            Some(true)
+        }
+
+        fn message(&self, _level: Level, _title: &str, _description: &str) {}
+
+        fn reject(&self, _title: &str, reason: &str) {
+            self.rejected
+                .lock()
+                .expect("not poisoned")
+                .push(reason.to_string());
+        }
+
+        fn keeps_history(&self) -> bool {
+            false
         }
     }
 
-    dialog
-}
+    #[test]
+    fn validation() {
+        let queue = Queue {
+            answers: Mutex::new(vec![
+                Some(vec!["a.csv".into(), "b.xlsx".into()]),
+                Some(vec!["a.xlsx".into(), "b.xlsx".into()]),
+                None,
+            ]),
+            rejected: Mutex::new(Vec::new()),
+        };
+        let xlsx = |path: &Path| {
+            if path.extension().is_some_and(|ext| ext == "xlsx") {
+                Ok(())
+            } else {
+                Err("no es xlsx".to_string())
+            }
+        };
 
-fn confirm_cancel() -> MessageDialogResult {
-    MessageDialog::new()
-        .set_title("Cancelar y salir")
-        .set_description("¿Seguro que quieres cancelar y terminar el programa?")
-        .set_buttons(rfd::MessageButtons::YesNo)
-        .show()
+        let files = PathRequest::new(PathKind::OpenFiles, "Archivos", None);
+        let paths = ask_until_valid(&queue, &files, &xlsx, true);
+        assert_eq!(paths, Some(vec!["a.xlsx".into(), "b.xlsx".into()]));
+        assert_eq!(
+            *queue.rejected.lock().expect("not poisoned"),
+            ["a.csv: no es xlsx"]
+        );
+
+        // Cancelling doesn't validate
+        let file = PathRequest::new(PathKind::OpenFile, "Archivo", None);
+        assert_eq!(ask_until_valid(&queue, &file, &xlsx, true), None);
+    }
+
+    #[test]
+    fn closing_without_confirmation() {
+        let queue = Queue {
+            answers: Mutex::new(vec![None, Some(vec!["a.xlsx".into()])]),
+            rejected: Mutex::new(Vec::new()),
+        };
+        let file = PathRequest::new(PathKind::OpenFile, "Archivo", None);
+
+        assert_eq!(ask_until_valid(&queue, &file, &|_| Ok(()), false), None);
+        // The next answer was not asked for
+        assert_eq!(queue.answers.lock().expect("not poisoned").len(), 1);
+    }
 }
@@ -0,0 +1,123 @@
+use rfd::{FileDialog, MessageButtons, MessageDialog, MessageDialogResult, MessageLevel};
+use std::{
+    ffi::OsStr,
+    path::{Path, PathBuf},
+};
+
+use crate::{msg, window, Level, PathKind, PathRequest, Prompter, Title};
+
+/// Native dialogs of the operating system through `rfd`
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NativePrompter;
+
+impl Prompter for NativePrompter {
+    fn ask_paths(&self, request: &PathRequest<'_>) -> Option<Vec<PathBuf>> {
+        let mut dialog =
+            append_filters(request.filters, FileDialog::new()).set_title(request.title);
+        if let Some(directory) = request.directory {
+            dialog = dialog.set_directory(directory);
+        }
+        if let Some(name) = preselected(request) {
+            dialog = dialog.set_file_name(name.to_string_lossy());
+        }
+
+        match request.kind {
+            PathKind::OpenFile => dialog.pick_file().map(|path| vec![path]),
+            PathKind::OpenFiles => dialog.pick_files(),
+            PathKind::OpenFolder => dialog.pick_folder().map(|path| vec![path]),
+            PathKind::OpenFolders => dialog.pick_folders(),
+            PathKind::SaveFile => dialog.save_file().map(|path| vec![path]),
+        }
+    }
+
+    fn confirm(&self, title: Title<'_>, description: &str) -> Option<bool> {
+        let res = MessageDialog::new()
+            .set_title(title.text)
+            .set_description(description)
+            .set_buttons(MessageButtons::YesNo)
+            .show();
+
+        Some(matches!(res, MessageDialogResult::Yes))
+    }
+
+    fn message(&self, level: Level, title: &str, description: &str) {
+        let level = match level {
+            Level::Info => MessageLevel::Info,
+            Level::Warning => MessageLevel::Warning,
+            Level::Error => MessageLevel::Error,
+        };
+
+        MessageDialog::new()
+            .set_level(level)
+            .set_title(title)
+            .set_description(description)
+            .show();
+    }
+
+    fn error(&self, message: &str, details: &str, log: Option<&Path>) {
+        if window::show_error(message, details, log).is_err() {
+            let description = log.map_or_else(
+                || details.to_string(),
+                |log| msg::DETAILS_IN.fill(log.display()),
+            );
+            self.message(Level::Error, message, &description);
+        }
+    }
+
+    fn is_native(&self) -> bool {
+        true
+    }
+}
+
+/// Name of the last file to pre-select. `rfd` can only start with one file name, so paths are only
+/// pre-selected for the questions of a single file. A file to open must be in the directory the
+/// dialog starts in, a name to save with is kept anywhere
+fn preselected<'a>(request: &PathRequest<'a>) -> Option<&'a OsStr> {
+    let last = request.last.first()?;
+    let in_directory = request
+        .directory
+        .is_none_or(|directory| last.parent() == Some(directory));
+
+    match request.kind {
+        PathKind::SaveFile => last.file_name(),
+        PathKind::OpenFile if in_directory => last.file_name(),
+        _ => None,
+    }
+}
+
+/// Append a collection of filters with (name, extensions) to a `FileDialog`
+fn append_filters(filters: Option<&[(&str, &[&str])]>, mut dialog: FileDialog) -> FileDialog {
+    if let Some(filters) = filters {
+        for (name, ext) in filters.iter().copied() {
+            dialog = dialog.add_filter(name, ext);
+        }
+    }
+
+    dialog
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn preselection() {
+        let dir = Path::new("datos");
+        let last = [dir.join("a.xlsx"), dir.join("b.xlsx")];
+        let mut request = PathRequest::new(PathKind::OpenFile, "Archivo", None);
+        request.last = &last;
+
+        assert_eq!(preselected(&request), Some(OsStr::new("a.xlsx")));
+        request.directory = Some(dir);
+        assert_eq!(preselected(&request), Some(OsStr::new("a.xlsx")));
+
+        // Another folder doesn't have the file, but it can be saved there with the same name
+        request.directory = Some(Path::new("otros"));
+        assert_eq!(preselected(&request), None);
+        request.kind = PathKind::SaveFile;
+        assert_eq!(preselected(&request), Some(OsStr::new("a.xlsx")));
+
+        request.kind = PathKind::OpenFiles;
+        assert_eq!(preselected(&request), None);
+    }
+}
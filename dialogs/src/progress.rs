@@ -0,0 +1,356 @@
+use indicatif::{MultiProgress, ProgressBar, ProgressDrawTarget, ProgressStyle};
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc, Mutex, MutexGuard, Once,
+};
+
+use crate::{msg, prompter, window};
+
+/// Where the progress is shown
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProgressMode {
+    /// A native window with a button to cancel, see [`crate::with_progress`] for its limits
+    Window,
+    /// Bars in stderr, Ctrl-C cancels
+    Terminal,
+}
+
+impl ProgressMode {
+    /// A window if the questions are shown with native dialogs and bars in the terminal otherwise
+    #[must_use]
+    pub fn auto() -> Self {
+        if prompter().is_native() {
+            Self::Window
+        } else {
+            Self::Terminal
+        }
+    }
+}
+
+/// Progress of a job made of tasks, like a group of files, that can be cancelled
+///
+/// It is cheap to clone and every clone reports to the same job, so it can be moved to the
+/// threads of the workers. Workers must check [`Progress::is_cancelled`] or
+/// [`Task::is_cancelled`] often and stop when it is true.
+#[derive(Debug, Clone)]
+pub struct Progress {
+    inner: Arc<Inner>,
+}
+
+#[derive(Debug)]
+struct Inner {
+    cancelled: AtomicBool,
+    done: AtomicBool,
+    tasks: Mutex<Vec<TaskState>>,
+    bars: Option<MultiProgress>,
+}
+
+/// What is shown for a task
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TaskState {
+    pub name: String,
+    pub position: u64,
+    pub total: u64,
+    /// What is being done, like the file being read
+    pub message: String,
+    pub finished: bool,
+}
+
+impl Progress {
+    fn new(bars: Option<MultiProgress>) -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                cancelled: AtomicBool::new(false),
+                done: AtomicBool::new(false),
+                tasks: Mutex::new(Vec::new()),
+                bars,
+            }),
+        }
+    }
+
+    /// Progress that isn't shown anywhere, for tests and jobs without user
+    #[must_use]
+    pub fn hidden() -> Self {
+        Self::new(None)
+    }
+
+    /// Start a task of `total` steps
+    pub fn task(&self, name: impl Into<String>, total: u64) -> Task {
+        let name = name.into();
+        let bar = self.inner.bars.as_ref().map(|bars| {
+            let bar = bars.add(ProgressBar::new(total));
+            bar.set_style(bar_style());
+            bar.set_prefix(name.clone());
+            bar
+        });
+
+        let mut tasks = self.tasks();
+        tasks.push(TaskState {
+            name,
+            total,
+            ..TaskState::default()
+        });
+
+        Task {
+            progress: self.clone(),
+            index: tasks.len() - 1,
+            bar,
+        }
+    }
+
+    /// Ask the workers to stop
+    pub fn cancel(&self) {
+        self.inner.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    #[must_use]
+    pub fn is_cancelled(&self) -> bool {
+        self.inner.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// Whether the job ended
+    #[must_use]
+    pub fn is_done(&self) -> bool {
+        self.inner.done.load(Ordering::Relaxed)
+    }
+
+    /// State of every task in the order they were started
+    #[must_use]
+    pub fn snapshot(&self) -> Vec<TaskState> {
+        self.tasks().clone()
+    }
+
+    /// Steps done and total steps of every task
+    #[must_use]
+    pub fn totals(&self) -> (u64, u64) {
+        self.tasks().iter().fold((0, 0), |(position, total), task| {
+            (position + task.position, total + task.total)
+        })
+    }
+
+    /// Print a line above the bars in the terminal, in a window it is only kept for the log of
+    /// errors
+    pub fn println(&self, line: impl AsRef<str>) {
+        let line = line.as_ref();
+        if let Some(bars) = &self.inner.bars {
+            let _ = bars.println(line);
+        }
+        crate::log(line);
+    }
+
+    fn tasks(&self) -> MutexGuard<'_, Vec<TaskState>> {
+        // A worker that panicked while updating its task leaves a valid state
+        self.inner
+            .tasks
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+
+    fn update(&self, index: usize, f: impl FnOnce(&mut TaskState)) {
+        if let Some(task) = self.tasks().get_mut(index) {
+            f(task);
+        }
+    }
+}
+
+/// One task of a [`Progress`], it is marked as finished when dropped
+#[derive(Debug)]
+pub struct Task {
+    progress: Progress,
+    index: usize,
+    bar: Option<ProgressBar>,
+}
+
+impl Task {
+    /// Advance `steps`
+    pub fn inc(&self, steps: u64) {
+        self.progress
+            .update(self.index, |task| task.position += steps);
+        if let Some(bar) = &self.bar {
+            bar.inc(steps);
+        }
+    }
+
+    /// Change the total of steps, like when they are known after reading the files
+    pub fn set_total(&self, total: u64) {
+        self.progress.update(self.index, |task| task.total = total);
+        if let Some(bar) = &self.bar {
+            bar.set_length(total);
+        }
+    }
+
+    /// Show what is being done, like the file being read
+    pub fn set_message(&self, message: impl Into<String>) {
+        let message = message.into();
+        if let Some(bar) = &self.bar {
+            bar.set_message(message.clone());
+        }
+        self.progress
+            .update(self.index, |task| task.message = message);
+    }
+
+    /// See [`Progress::println`]
+    pub fn println(&self, line: impl AsRef<str>) {
+        self.progress.println(line);
+    }
+
+    #[must_use]
+    pub fn is_cancelled(&self) -> bool {
+        self.progress.is_cancelled()
+    }
+}
+
+impl Drop for Task {
+    fn drop(&mut self) {
+        self.progress
+            .update(self.index, |task| task.finished = true);
+        if let Some(bar) = &self.bar {
+            bar.finish();
+        }
+    }
+}
+
+fn bar_style() -> ProgressStyle {
+    ProgressStyle::with_template("{prefix:.bold} [{bar:30}] {pos}/{len} {wide_msg}")
+        .unwrap_or_else(|_| ProgressStyle::default_bar())
+        .progress_chars("=> ")
+}
+
+/// Marks the job as done even if it panics, so the window closes
+struct DoneGuard<'a>(&'a Progress);
+
+impl Drop for DoneGuard<'_> {
+    fn drop(&mut self) {
+        self.0.inner.done.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Lets Ctrl-C cancel `progress` until it is dropped, even if the job panics
+struct InterruptGuard;
+
+impl InterruptGuard {
+    fn new(progress: &Progress) -> Self {
+        set_interrupted(Some(progress.clone()));
+        Self
+    }
+}
+
+impl Drop for InterruptGuard {
+    fn drop(&mut self) {
+        set_interrupted(None);
+    }
+}
+
+/// Run `job` showing its progress in `mode`, returning what it returns
+///
+/// The window is shown in the calling thread while `job` runs in another one, so it must be
+/// called from the main thread and, because the window library can't start twice, only once per
+/// program. Later windows, like the details of [`crate::report_error`], fall back to simple
+/// native messages. If the window can't be shown the job runs without it. Closing the window
+/// cancels the job and waits for it to stop.
+///
+/// Errors and panics that the job reports from other threads are shown when it ends.
+///
+/// # Panics
+///
+/// If `job` panics, the panic is resumed in the calling thread
+pub fn with_progress<T: Send>(
+    mode: ProgressMode,
+    title: &str,
+    job: impl FnOnce(&Progress) -> T + Send,
+) -> T {
+    match mode {
+        ProgressMode::Window => {
+            let progress = Progress::hidden();
+            std::thread::scope(|s| {
+                let worker = s.spawn(|| {
+                    let _done = DoneGuard(&progress);
+                    job(&progress)
+                });
+
+                if let Err(err) = window::show(title, &progress) {
+                    eprintln!("{}", msg::NO_PROGRESS_WINDOW.fill(err));
+                }
+
+                let res = worker.join();
+                crate::report::show_pending();
+                res.unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+            })
+        }
+        ProgressMode::Terminal => {
+            let bars = MultiProgress::with_draw_target(ProgressDrawTarget::stderr());
+            let progress = Progress::new(Some(bars));
+            eprintln!("{title}");
+
+            let _interrupt = InterruptGuard::new(&progress);
+            let _done = DoneGuard(&progress);
+            let res = job(&progress);
+            crate::report::show_pending();
+
+            res
+        }
+    }
+}
+
+/// Progress cancelled by Ctrl-C, without one Ctrl-C ends the program as usual
+static INTERRUPTED: Mutex<Option<Progress>> = Mutex::new(None);
+
+fn set_interrupted(progress: Option<Progress>) {
+    static HANDLER: Once = Once::new();
+    HANDLER.call_once(|| {
+        let _ = ctrlc::set_handler(|| {
+            let current = INTERRUPTED
+                .lock()
+                .unwrap_or_else(std::sync::PoisonError::into_inner);
+            match current.as_ref() {
+                Some(progress) => progress.cancel(),
+                None => std::process::exit(130),
+            }
+        });
+    });
+
+    *INTERRUPTED
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner) = progress;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tasks() {
+        let progress = Progress::hidden();
+
+        std::thread::scope(|s| {
+            for (name, files) in [("a", 2), ("b", 3)] {
+                let task = progress.task(name, files);
+                s.spawn(move || {
+                    for file in 0..files {
+                        task.set_message(format!("{file}.xlsx"));
+                        task.inc(1);
+                    }
+                });
+            }
+        });
+
+        assert_eq!(progress.totals(), (5, 5));
+        let tasks = progress.snapshot();
+        assert!(tasks.iter().all(|task| task.finished));
+        assert_eq!(tasks[1].message, "2.xlsx");
+
+        let task = progress.task("c", 1);
+        progress.cancel();
+        assert!(task.is_cancelled());
+    }
+
+    #[test]
+    fn panic_stops_interrupting() {
+        let res = std::panic::catch_unwind(|| {
+            with_progress(ProgressMode::Terminal, "Trabajo", |_| panic!("falla"));
+        });
+
+        assert!(res.is_err());
+        assert!(INTERRUPTED.lock().expect("not poisoned").is_none());
+    }
+}
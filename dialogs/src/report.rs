@@ -0,0 +1,209 @@
+use std::{
+    backtrace::Backtrace,
+    fmt::Write as _,
+    fs::OpenOptions,
+    io::Write as _,
+    panic::PanicHookInfo,
+    path::{Path, PathBuf},
+    sync::{Mutex, PoisonError},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::{msg, prompter};
+
+/// Folder of the log, set by the tool once it knows where it writes
+static LOG_DIR: Mutex<Option<PathBuf>> = Mutex::new(None);
+/// Lines logged by the tool, written with every error
+static LOG: Mutex<Vec<String>> = Mutex::new(Vec::new());
+/// Errors of other threads to show from the main thread, see [`show_pending`]
+static PENDING: Mutex<Vec<Report>> = Mutex::new(Vec::new());
+
+struct Report {
+    message: String,
+    details: String,
+    log: Option<PathBuf>,
+}
+
+/// Write the error logs in `dir`, usually the folder of the output. Until it is set they are
+/// written in the temporary folder
+pub fn set_log_dir(dir: impl Into<PathBuf>) {
+    *LOG_DIR.lock().unwrap_or_else(PoisonError::into_inner) = Some(dir.into());
+}
+
+/// Keep `line` to write it in the log if there is an error
+pub fn log(line: impl Into<String>) {
+    LOG.lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .push(line.into());
+}
+
+/// Tell the user about an error: `message` is shown and `details` can be expanded, both are
+/// appended with the logged lines to `<tool>.log` in the log folder. Returns the path of the log
+/// if it could be written
+///
+/// Windows can only be shown from the main thread, in other threads the error is shown by
+/// [`crate::with_progress`] when its job ends
+#[allow(clippy::must_use_candidate)]
+pub fn report_error(message: &str, details: &str) -> Option<PathBuf> {
+    let log = write_log(message, details);
+    show(Report {
+        message: message.to_string(),
+        details: details.to_string(),
+        log: log.clone(),
+    });
+    log
+}
+
+/// Report panics with [`report_error`], for tools without a console where the panic message is
+/// lost. The previous hook still runs after it
+pub fn install_panic_hook() {
+    let previous = std::panic::take_hook();
+
+    std::panic::set_hook(Box::new(move |info| {
+        let message = msg::UNEXPECTED.get();
+        let details = panic_details(info);
+        let log = write_log(message, &details);
+        show(Report {
+            message: message.to_string(),
+            details,
+            log,
+        });
+
+        previous(info);
+    }));
+}
+
+/// Show the errors that other threads reported while a native window may have been open
+pub(crate) fn show_pending() {
+    let pending = std::mem::take(&mut *PENDING.lock().unwrap_or_else(PoisonError::into_inner));
+    for report in pending {
+        prompter().error(&report.message, &report.details, report.log.as_deref());
+    }
+}
+
+/// Show `report` now if it can be, otherwise keep it for [`show_pending`]
+fn show(report: Report) {
+    let Report {
+        message,
+        details,
+        log,
+    } = &report;
+
+    // The window library only works in the main thread, so other threads only print and log
+    if !prompter().is_native() || std::thread::current().name() == Some("main") {
+        prompter().error(message, details, log.as_deref());
+        return;
+    }
+
+    eprintln!("{}: {message}", msg::ERROR);
+    if let Some(log) = log {
+        eprintln!("{}", msg::DETAILS_IN.fill(log.display()));
+    }
+    PENDING
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .push(report);
+}
+
+fn panic_details(info: &PanicHookInfo<'_>) -> String {
+    let payload = info
+        .payload()
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| info.payload().downcast_ref::<String>().map(String::as_str))
+        .unwrap_or(msg::NO_PANIC_MESSAGE.get());
+    let location = info
+        .location()
+        .map(|l| format!(" {}:{}", l.file(), l.line()))
+        .unwrap_or_default();
+    let thread = std::thread::current();
+
+    format!(
+        "{} {:?}{location}: {payload}\n\n{}",
+        msg::THREAD,
+        thread.name().unwrap_or_default(),
+        Backtrace::force_capture()
+    )
+}
+
+/// Append the error to the log, returning its path
+fn write_log(message: &str, details: &str) -> Option<PathBuf> {
+    let dir = LOG_DIR
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .clone()
+        .unwrap_or_else(std::env::temp_dir);
+    let exe = std::env::current_exe().ok();
+    let tool = exe
+        .as_deref()
+        .and_then(Path::file_stem)
+        .map_or_else(|| "dialogs".into(), |stem| stem.to_string_lossy());
+    let path = dir.join(format!("{tool}.log"));
+
+    let mut text = String::new();
+    let _ = writeln!(text, "==== {} ====", now());
+    let args = std::env::args().collect::<Vec<_>>();
+    let _ = writeln!(text, "{}: {args:?}", msg::ARGUMENTS);
+    let _ = writeln!(text, "{}: {message}", msg::ERROR);
+    let _ = writeln!(text, "\n{details}");
+    let lines = LOG.lock().unwrap_or_else(PoisonError::into_inner);
+    if !lines.is_empty() {
+        let _ = writeln!(text, "\n{}:", msg::LOG);
+        for line in lines.iter() {
+            let _ = writeln!(text, "  {line}");
+        }
+    }
+    drop(lines);
+    text.push('\n');
+
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .and_then(|mut file| file.write_all(text.as_bytes()))
+        .ok()
+        .map(|()| path)
+}
+
+/// Current UTC date and time like `2024-06-30 18:05:09 UTC`
+fn now() -> String {
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs());
+    let (year, month, day) = civil_from_days(secs / 86_400);
+    let secs = secs % 86_400;
+
+    format!(
+        "{year}-{month:02}-{day:02} {:02}:{:02}:{:02} UTC",
+        secs / 3600,
+        secs / 60 % 60,
+        secs % 60
+    )
+}
+
+/// Date of a number of days since 1970-01-01, from Howard Hinnant's `civil_from_days`
+fn civil_from_days(days: u64) -> (u64, u64, u64) {
+    let z = days + 719_468;
+    let era = z / 146_097;
+    let doe = z % 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + u64::from(month <= 2);
+
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dates() {
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+        assert_eq!(civil_from_days(19_783), (2024, 3, 1));
+        assert_eq!(civil_from_days(20_454), (2026, 1, 1));
+    }
+}
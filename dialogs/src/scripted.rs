@@ -0,0 +1,228 @@
+use serde::Deserialize;
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+use thiserror::Error;
+
+use crate::{msg, Level, PathRequest, Prompter, Text, Title};
+
+/// Environment variable with the answers for [`ScriptedPrompter`], the path of a `.json` or
+/// `.toml` file or the JSON itself
+pub const ANSWERS_VAR: &str = "DIALOGS_ANSWERS";
+
+#[derive(Error, Debug)]
+pub enum AnswersError {
+    #[error("{}: {1}", msg::ANSWERS_NOT_READ.fill(.0.display()))]
+    Io(PathBuf, std::io::Error),
+    #[error("{}", msg::INVALID_JSON_ANSWERS.fill(.0))]
+    Json(#[from] serde_json::Error),
+    #[error("{}", msg::INVALID_TOML_ANSWERS.fill(.0))]
+    Toml(#[from] toml::de::Error),
+    #[error("{}", msg::NO_ANSWER.fill(.0))]
+    Missing(String),
+    #[error("{} {1}", msg::ANSWER_NOT_KIND.fill(.0))]
+    Kind(String, Text),
+    #[error("{}: {1}", msg::ANSWER_INVALID.fill(.0))]
+    Invalid(String, String),
+}
+
+/// Recorded answer to a question
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(untagged)]
+pub enum Answer {
+    /// Answer to a yes or no question, `false` also closes a path question without choosing
+    Yes(bool),
+    Path(PathBuf),
+    Paths(Vec<PathBuf>),
+}
+
+/// Answers read from a file instead of asking, keyed by the [`Title`] key of each question, its
+/// Spanish title in every language
+///
+/// A question without answer or with an answer of the wrong kind ends the program with an error,
+/// so batch jobs fail instead of waiting for someone. Messages are written to stderr.
+///
+/// ```
+/// use dialogs::{PathKind, PathRequest, Prompter, ScriptedPrompter};
+/// use std::path::PathBuf;
+///
+/// let answers = ScriptedPrompter::from_json(
+///     r#"{ "Archivos": ["a.xlsx", "b.xlsx"], "Salida": "c.xlsx", "Descartados": false }"#,
+/// )?;
+///
+/// let request = PathRequest::new(PathKind::SaveFile, "Salida", None);
+/// assert_eq!(answers.ask_paths(&request), Some(vec![PathBuf::from("c.xlsx")]));
+/// assert_eq!(answers.confirm("Descartados".into(), ""), Some(false));
+/// # Ok::<(), dialogs::AnswersError>(())
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ScriptedPrompter {
+    answers: HashMap<String, Answer>,
+}
+
+impl ScriptedPrompter {
+    /// Answers from [`ANSWERS_VAR`], None if it isn't set
+    ///
+    /// # Errors
+    ///
+    /// If the file can't be read or the answers are invalid
+    pub fn from_env() -> Option<Result<Self, AnswersError>> {
+        let value = std::env::var_os(ANSWERS_VAR).filter(|v| !v.is_empty())?;
+
+        Some(match value.to_str().map(str::trim_start) {
+            Some(json) if json.starts_with('{') => Self::from_json(json),
+            _ => Self::from_file(value),
+        })
+    }
+
+    /// Answers from a TOML file if its extension is `toml` and from a JSON file otherwise
+    ///
+    /// # Errors
+    ///
+    /// If the file can't be read or the answers are invalid
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, AnswersError> {
+        let path = path.as_ref();
+        let text =
+            std::fs::read_to_string(path).map_err(|err| AnswersError::Io(path.into(), err))?;
+
+        if path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("toml"))
+        {
+            Self::from_toml(&text)
+        } else {
+            Self::from_json(&text)
+        }
+    }
+
+    /// # Errors
+    ///
+    /// If the text isn't an object of answers
+    pub fn from_json(json: &str) -> Result<Self, AnswersError> {
+        Ok(Self {
+            answers: serde_json::from_str(json)?,
+        })
+    }
+
+    /// # Errors
+    ///
+    /// If the text isn't a table of answers
+    pub fn from_toml(toml: &str) -> Result<Self, AnswersError> {
+        Ok(Self {
+            answers: toml::from_str(toml)?,
+        })
+    }
+
+    /// Paths answered for `request`, None if the answer is `false`
+    ///
+    /// # Errors
+    ///
+    /// If there is no answer, it is `true` or it has many paths for a single path question
+    pub fn paths(&self, request: &PathRequest<'_>) -> Result<Option<Vec<PathBuf>>, AnswersError> {
+        let kind_error = || {
+            let kind = if request.kind.is_multiple() {
+                msg::KIND_PATHS
+            } else {
+                msg::KIND_PATH
+            };
+            AnswersError::Kind(request.key.to_string(), kind)
+        };
+
+        match self.answer(request.key)? {
+            Answer::Yes(false) => Ok(None),
+            Answer::Path(path) => Ok(Some(vec![path.clone()])),
+            Answer::Paths(paths) if request.kind.is_multiple() || paths.len() == 1 => {
+                Ok(Some(paths.clone()))
+            }
+            Answer::Yes(true) | Answer::Paths(_) => Err(kind_error()),
+        }
+    }
+
+    /// Answer to a yes or no question
+    ///
+    /// # Errors
+    ///
+    /// If there is no answer or it isn't a bool
+    pub fn yes(&self, key: &str) -> Result<bool, AnswersError> {
+        match self.answer(key)? {
+            Answer::Yes(yes) => Ok(*yes),
+            _ => Err(AnswersError::Kind(key.to_string(), msg::KIND_YES_NO)),
+        }
+    }
+
+    fn answer(&self, key: &str) -> Result<&Answer, AnswersError> {
+        self.answers
+            .get(key)
+            .ok_or_else(|| AnswersError::Missing(key.to_string()))
+    }
+}
+
+impl Prompter for ScriptedPrompter {
+    fn ask_paths(&self, request: &PathRequest<'_>) -> Option<Vec<PathBuf>> {
+        self.paths(request).unwrap_or_else(|err| fail(&err))
+    }
+
+    fn confirm(&self, title: Title<'_>, _description: &str) -> Option<bool> {
+        Some(self.yes(title.key).unwrap_or_else(|err| fail(&err)))
+    }
+
+    fn message(&self, level: Level, title: &str, description: &str) {
+        crate::terminal::print_message(level, title, description);
+    }
+
+    /// The answer can't change, so asking again would never end
+    fn reject(&self, title: &str, reason: &str) {
+        fail(&AnswersError::Invalid(
+            title.to_string(),
+            reason.to_string(),
+        ));
+    }
+
+    fn keeps_history(&self) -> bool {
+        false
+    }
+}
+
+/// End the program because the answers don't match the questions
+pub(crate) fn fail(err: &AnswersError) -> ! {
+    eprintln!("Error: {err}");
+    std::process::exit(2)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PathKind;
+
+    #[test]
+    fn answers() {
+        let answers = ScriptedPrompter::from_toml(
+            r#"
+            "Selecciona los archivos" = ["a.xlsx", "b.xlsx"]
+            "Configuración" = "config.xlsx"
+            "Cancelar y salir" = true
+            "#,
+        )
+        .expect("valid TOML");
+
+        let request = |kind, title| PathRequest::new(kind, title, None);
+
+        assert_eq!(
+            answers
+                .paths(&request(PathKind::OpenFiles, "Configuración"))
+                .expect("answered"),
+            Some(vec![PathBuf::from("config.xlsx")])
+        );
+        assert!(matches!(
+            answers.paths(&request(PathKind::OpenFile, "Selecciona los archivos")),
+            Err(AnswersError::Kind(..))
+        ));
+        assert!(matches!(
+            answers.paths(&request(PathKind::SaveFile, "Salida")),
+            Err(AnswersError::Missing(title)) if title == "Salida"
+        ));
+        assert!(answers.yes("Cancelar y salir").expect("answered"));
+        assert!(answers.yes("Configuración").is_err());
+    }
+}
@@ -0,0 +1,314 @@
+use rustyline::{
+    completion::{Completer, FilenameCompleter, Pair},
+    error::ReadlineError,
+    highlight::Highlighter,
+    hint::Hinter,
+    history::DefaultHistory,
+    validate::Validator,
+    CompletionType, Config, Context, Editor, Helper,
+};
+use std::{
+    io::{self, IsTerminal},
+    path::{Path, PathBuf, MAIN_SEPARATOR},
+};
+
+use crate::{msg, Level, PathKind, PathRequest, Prompter, Title};
+
+/// Questions in the terminal for when there is no display, paths are completed with tab
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TerminalPrompter;
+
+impl Prompter for TerminalPrompter {
+    fn ask_paths(&self, request: &PathRequest<'_>) -> Option<Vec<PathBuf>> {
+        let mut editor = editor().ok()?;
+        editor.set_helper(Some(PathHelper(FilenameCompleter::new())));
+
+        eprintln!("{}", request.title);
+        if let Some(filters) = request.filters.filter(|f| !f.is_empty()) {
+            let names = filters
+                .iter()
+                .map(|(name, ext)| format!("{name} (*.{})", ext.join(", *.")))
+                .collect::<Vec<_>>();
+            eprintln!("  {}", names.join(", "));
+        }
+
+        let mut paths = Vec::new();
+        loop {
+            let prompt = if request.kind.is_multiple() {
+                msg::PATHS_PROMPT.fill(paths.len() + 1)
+            } else {
+                msg::PATH_PROMPT.get().to_string()
+            };
+
+            let initial = initial_text(request, paths.len());
+            let line = match editor.readline_with_initial(&prompt, (&initial, "")) {
+                Ok(line) => line,
+                Err(ReadlineError::Eof | ReadlineError::Interrupted) => return None,
+                Err(err) => {
+                    eprintln!("{}: {err}", msg::ERROR);
+                    return None;
+                }
+            };
+            if line.trim().is_empty() {
+                return (!paths.is_empty()).then_some(paths);
+            }
+
+            match check_path(request, parse_path(&line)) {
+                Ok(path) => {
+                    if request.kind == PathKind::SaveFile
+                        && path.exists()
+                        && !self
+                            .confirm(
+                                msg::REPLACE_TITLE.into(),
+                                &msg::REPLACE_DESCRIPTION.fill(path.display()),
+                            )
+                            .unwrap_or(false)
+                    {
+                        continue;
+                    }
+
+                    let _ = editor.add_history_entry(line);
+                    paths.push(path);
+                    if !request.kind.is_multiple() {
+                        return Some(paths);
+                    }
+                }
+                Err(msg) => eprintln!("  {msg}"),
+            }
+        }
+    }
+
+    fn confirm(&self, title: Title<'_>, description: &str) -> Option<bool> {
+        let mut editor = editor::<()>().ok()?;
+
+        loop {
+            let line = editor
+                .readline(&format!("{}. {description} {}: ", title.text, msg::YES_NO))
+                .ok()?;
+
+            match line.trim().to_lowercase().as_str() {
+                "s" | "si" | "sí" | "y" | "yes" => break Some(true),
+                "n" | "no" => break Some(false),
+                _ => eprintln!("  {}", msg::ANSWER_YES_NO),
+            }
+        }
+    }
+
+    fn message(&self, level: Level, title: &str, description: &str) {
+        print_message(level, title, description);
+    }
+
+    fn supports_form(&self) -> bool {
+        io::stdin().is_terminal() && io::stderr().is_terminal()
+    }
+}
+
+pub(crate) fn print_message(level: Level, title: &str, description: &str) {
+    let prefix = match level {
+        Level::Info => String::new(),
+        Level::Warning => format!("{}: ", msg::WARNING),
+        Level::Error => format!("{}: ", msg::ERROR),
+    };
+
+    if description.is_empty() {
+        eprintln!("{prefix}{title}");
+    } else {
+        eprintln!("{prefix}{title}. {description}");
+    }
+}
+
+fn editor<H: Helper>() -> rustyline::Result<Editor<H, DefaultHistory>> {
+    let config = Config::builder()
+        .auto_add_history(false)
+        .completion_type(CompletionType::List)
+        .build();
+
+    Editor::with_config(config)
+}
+
+/// Completes file names, the rest of the helper traits do nothing
+struct PathHelper(FilenameCompleter);
+
+impl Completer for PathHelper {
+    type Candidate = Pair;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        ctx: &Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<Pair>)> {
+        self.0.complete(line, pos, ctx)
+    }
+}
+
+impl Hinter for PathHelper {
+    type Hint = String;
+}
+
+impl Highlighter for PathHelper {}
+
+impl Validator for PathHelper {}
+
+impl Helper for PathHelper {}
+
+/// Text to start the `n`th answer with: the `n`th path of the last time or, for the first answer,
+/// the last directory
+fn initial_text(request: &PathRequest<'_>, n: usize) -> String {
+    if let Some(last) = request.last.get(n) {
+        return last.display().to_string();
+    }
+
+    match request.directory {
+        Some(dir) if n == 0 => format!("{}{MAIN_SEPARATOR}", dir.display()),
+        _ => String::new(),
+    }
+}
+
+/// Path written by the user. Quotes around it are removed, as added by terminals when a file is
+/// dropped, a leading `~` is the home folder and on Unix the `\` escapes of the completion are
+/// removed
+pub(crate) fn parse_path(line: &str) -> PathBuf {
+    let line = line.trim();
+    let unquoted = ['"', '\'']
+        .into_iter()
+        .find_map(|q| line.strip_prefix(q).and_then(|l| l.strip_suffix(q)));
+
+    let text = match unquoted {
+        Some(text) => text.to_string(),
+        None if cfg!(unix) => {
+            let mut res = String::with_capacity(line.len());
+            let mut chars = line.chars();
+            while let Some(c) = chars.next() {
+                res.push(if c == '\\' {
+                    chars.next().unwrap_or(c)
+                } else {
+                    c
+                });
+            }
+            res
+        }
+        None => line.to_string(),
+    };
+
+    match text.strip_prefix('~') {
+        Some(rest) if rest.is_empty() || rest.starts_with(['/', '\\']) => dirs::home_dir()
+            .map_or_else(
+                || PathBuf::from(&text),
+                |home| home.join(rest.trim_start_matches(['/', '\\'])),
+            ),
+        _ => PathBuf::from(text),
+    }
+}
+
+/// Check that `path` can be chosen for `request`, returning the path to use or why it can't be
+/// chosen. A file to save without extension gets the first one of the filters
+fn check_path(request: &PathRequest<'_>, path: PathBuf) -> Result<PathBuf, String> {
+    let filters = request.filters.unwrap_or_default();
+
+    if request.kind.is_folder() {
+        return if path.is_dir() {
+            Ok(path)
+        } else {
+            Err(msg::NO_FOLDER.fill(path.display()))
+        };
+    }
+
+    if request.kind == PathKind::SaveFile {
+        let mut path = path;
+        if path.is_dir() {
+            return Err(msg::IS_FOLDER.fill(path.display()));
+        }
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            if !parent.is_dir() {
+                return Err(msg::NO_FOLDER.fill(parent.display()));
+            }
+        }
+        if path.extension().is_none() {
+            if let Some(ext) = filters
+                .iter()
+                .flat_map(|(_, ext)| ext.iter())
+                .find(|ext| **ext != "*")
+            {
+                path.set_extension(ext);
+            }
+        }
+        return if allowed(filters, &path) {
+            Ok(path)
+        } else {
+            Err(msg::BAD_EXTENSION.fill(path.display()))
+        };
+    }
+
+    if !path.is_file() {
+        Err(msg::NO_FILE.fill(path.display()))
+    } else if !allowed(filters, &path) {
+        Err(msg::BAD_EXTENSION.fill(path.display()))
+    } else {
+        Ok(path)
+    }
+}
+
+/// Whether the extension of `path` is in any filter. Without filters every path is allowed
+fn allowed(filters: &[(&str, &[&str])], path: &Path) -> bool {
+    let ext = path
+        .extension()
+        .map(|ext| ext.to_string_lossy())
+        .unwrap_or_default();
+
+    filters.is_empty()
+        || filters
+            .iter()
+            .flat_map(|(_, exts)| exts.iter())
+            .any(|allowed| *allowed == "*" || allowed.eq_ignore_ascii_case(&ext))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXCEL: &[(&str, &[&str])] = &[("Excel", &["xlsx", "xls"])];
+
+    #[test]
+    fn paths() {
+        assert_eq!(parse_path(" 'a b/c.xlsx' "), PathBuf::from("a b/c.xlsx"));
+        if cfg!(unix) {
+            assert_eq!(parse_path(r"a\ b/c.xlsx"), PathBuf::from("a b/c.xlsx"));
+        }
+        if let Some(home) = dirs::home_dir() {
+            assert_eq!(parse_path("~/x.csv"), home.join("x.csv"));
+        }
+        assert_eq!(parse_path("~x"), PathBuf::from("~x"));
+    }
+
+    #[test]
+    fn checks() {
+        let dir = std::env::temp_dir();
+        let request = |kind| PathRequest::new(kind, "", Some(EXCEL));
+
+        assert_eq!(
+            check_path(&request(PathKind::SaveFile), dir.join("reporte")),
+            Ok(dir.join("reporte.xlsx"))
+        );
+        assert!(check_path(&request(PathKind::SaveFile), dir.join("reporte.csv")).is_err());
+        assert!(check_path(&request(PathKind::SaveFile), dir.join("no/existe.xlsx")).is_err());
+        assert!(check_path(&request(PathKind::OpenFolder), dir.clone()).is_ok());
+        assert!(check_path(&request(PathKind::OpenFile), dir.clone()).is_err());
+
+        let last = [dir.join("a.xlsx"), dir.join("b.xlsx")];
+        let mut files = request(PathKind::OpenFiles);
+        files.directory = Some(&dir);
+        files.last = &last;
+        assert_eq!(initial_text(&files, 1), last[1].display().to_string());
+        assert_eq!(initial_text(&files, 2), "");
+        files.last = &[];
+        assert_eq!(
+            initial_text(&files, 0),
+            format!("{}{MAIN_SEPARATOR}", dir.display())
+        );
+
+        assert!(allowed(EXCEL, Path::new("a.XLSX")));
+        assert!(!allowed(EXCEL, Path::new("a")));
+        assert!(allowed(&[], Path::new("a")));
+    }
+}
@@ -0,0 +1,157 @@
+use eframe::egui;
+use std::{path::Path, time::Duration};
+
+use crate::{msg, Progress};
+
+/// Show the progress in a window until the job is done
+pub(crate) fn show(title: &str, progress: &Progress) -> Result<(), eframe::Error> {
+    let options = eframe::NativeOptions {
+        viewport: egui::ViewportBuilder::default()
+            .with_title(title)
+            .with_inner_size([480., 320.])
+            .with_min_inner_size([320., 160.]),
+        centered: true,
+        ..Default::default()
+    };
+
+    let app = ProgressApp {
+        title: title.to_string(),
+        progress: progress.clone(),
+    };
+
+    eframe::run_native(title, options, Box::new(|_| Box::new(app)))
+}
+
+/// Show an error with its details collapsed until the user closes the window. Fails if a window
+/// was already shown, the window library can't start twice in a program
+pub(crate) fn show_error(
+    message: &str,
+    details: &str,
+    log: Option<&Path>,
+) -> Result<(), eframe::Error> {
+    let options = eframe::NativeOptions {
+        viewport: egui::ViewportBuilder::default()
+            .with_title(msg::ERROR.get())
+            .with_inner_size([560., 220.]),
+        centered: true,
+        ..Default::default()
+    };
+
+    let app = ErrorApp {
+        message: message.to_string(),
+        details: details.to_string(),
+        log: log.map(|log| msg::DETAILS_IN.fill(log.display())),
+    };
+
+    eframe::run_native(msg::ERROR.get(), options, Box::new(|_| Box::new(app)))
+}
+
+struct ErrorApp {
+    message: String,
+    details: String,
+    log: Option<String>,
+}
+
+impl eframe::App for ErrorApp {
+    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        egui::TopBottomPanel::bottom("botones").show(ctx, |ui| {
+            ui.add_space(4.);
+            ui.horizontal(|ui| {
+                if ui.button(msg::CLOSE.get()).clicked() {
+                    ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+                }
+                if ui.button(msg::COPY_DETAILS.get()).clicked() {
+                    ui.output_mut(|o| o.copied_text.clone_from(&self.details));
+                }
+            });
+            ui.add_space(4.);
+        });
+
+        egui::CentralPanel::default().show(ctx, |ui| {
+            ui.heading(&self.message);
+            if let Some(log) = &self.log {
+                ui.label(log);
+            }
+            ui.separator();
+
+            egui::CollapsingHeader::new(msg::DETAILS.get()).show(ui, |ui| {
+                egui::ScrollArea::both().show(ui, |ui| {
+                    ui.add(
+                        egui::TextEdit::multiline(&mut self.details.as_str())
+                            .code_editor()
+                            .desired_width(f32::INFINITY),
+                    );
+                });
+            });
+        });
+    }
+}
+
+struct ProgressApp {
+    title: String,
+    progress: Progress,
+}
+
+impl eframe::App for ProgressApp {
+    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        if self.progress.is_done() {
+            ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+            return;
+        }
+
+        // Closing the window cancels, it is closed when the workers stop
+        if ctx.input(|i| i.viewport().close_requested()) {
+            self.progress.cancel();
+            ctx.send_viewport_cmd(egui::ViewportCommand::CancelClose);
+        }
+
+        let cancelled = self.progress.is_cancelled();
+        let (position, total) = self.progress.totals();
+
+        egui::TopBottomPanel::bottom("cancelar").show(ctx, |ui| {
+            ui.add_space(4.);
+            let text = if cancelled {
+                msg::CANCELLING
+            } else {
+                msg::CANCEL
+            };
+            if ui
+                .add_enabled(!cancelled, egui::Button::new(text.get()))
+                .clicked()
+            {
+                self.progress.cancel();
+            }
+            ui.add_space(4.);
+        });
+
+        egui::CentralPanel::default().show(ctx, |ui| {
+            ui.heading(&self.title);
+            ui.add(egui::ProgressBar::new(fraction(position, total)).show_percentage());
+            ui.separator();
+
+            egui::ScrollArea::vertical().show(ui, |ui| {
+                for task in self.progress.snapshot() {
+                    ui.label(format!("{} ({}/{})", task.name, task.position, task.total));
+                    let bar = egui::ProgressBar::new(fraction(task.position, task.total));
+                    let text = if task.finished {
+                        msg::DONE.get().to_string()
+                    } else {
+                        task.message
+                    };
+                    ui.add(bar.text(text));
+                }
+            });
+        });
+
+        ctx.request_repaint_after(Duration::from_millis(100));
+    }
+}
+
+#[allow(clippy::cast_precision_loss, clippy::cast_possible_truncation)]
+fn fraction(position: u64, total: u64) -> f32 {
+    if total == 0 {
+        0.
+    } else {
+        (position as f64 / total as f64).min(1.) as f32
+    }
+}
@@ -1,9 +1,8 @@
-use ::strings::{get_words, n_chars, space_join};
+use ::strings::names::FullName;
 use calamine::Xlsx;
 use itertools::izip;
 use polars::{lazy::dsl::*, prelude::*};
-use polars_excel_writer::PolarsXlsxWriter;
-use polars_sheet_reader::{read_set_from_sheet, read_sheet, read_sheet_nth};
+use polars_sheet_reader::{read_set_from_sheet, read_sheet, read_sheet_nth, write_xlsx, DipeExpr};
 use std::{path::PathBuf, str::FromStr};
 
 fn main() {
@@ -79,22 +78,7 @@ fn main() {
                 col("Id Profesor").cast(DataType::UInt64),
                 col("Class Id").cast(DataType::UInt64),
                 col("Id Curso").cast(DataType::UInt64),
-                col("Nombre").map(
-                    |s| {
-                        Ok(Some(Series::from_iter(s.str().unwrap().into_iter().map(
-                            |a| {
-                                let a = a.unwrap_or_default();
-
-                                let (apellidos, nombres) = a.split_once(',').unwrap();
-                                let apellidos = n_chars(space_join(get_words(apellidos)), 2);
-                                let nombres = n_chars(space_join(get_words(nombres).take(1)), 2);
-
-                                String::from_iter(nombres.chain([' ']).chain(apellidos))
-                            },
-                        ))))
-                    },
-                    GetOutput::from_type(DataType::String),
-                ),
+                col("Nombre").dipe().initials_key(2),
             ])
             .collect()
             .unwrap();
@@ -145,18 +129,12 @@ fn main() {
 
                         let out: StringChunked = izip!(s_nombre, s_apellido_pat, s_apellido_mat)
                             .map(|(nombre, pat, mat)| {
-                                let mat =
-                                    n_chars(space_join(get_words(mat.unwrap_or_default())), 2);
-                                let pat =
-                                    n_chars(space_join(get_words(pat.unwrap_or_default())), 2);
-                                let nombre = n_chars(
-                                    space_join(get_words(nombre.unwrap_or_default()).take(1)),
-                                    2,
-                                );
-
-                                String::from_iter(
-                                    nombre.chain([' ']).chain(pat).chain([' ']).chain(mat),
+                                FullName::from_parts(
+                                    nombre.unwrap_or_default(),
+                                    pat.unwrap_or_default(),
+                                    mat.unwrap_or_default(),
                                 )
+                                .initials_key(2)
                             })
                             .collect();
 
@@ -459,11 +437,5 @@ fn main() {
 
     let df = df.fill_null(FillNullStrategy::Zero).unwrap();
 
-    write_xlsx(df, "dataframe.xlsx");
-}
-
-fn write_xlsx(df: DataFrame, name: &str) {
-    let mut writer = PolarsXlsxWriter::new();
-    writer.write_dataframe(&df).unwrap();
-    writer.save(name).unwrap();
+    write_xlsx(&df, "dataframe.xlsx", false).unwrap();
 }
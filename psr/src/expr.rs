@@ -0,0 +1,340 @@
+use ::strings::{
+    fix_mojibake,
+    ids::{Curp, Cvu, Rfc, Sex},
+    names::FullName,
+    numbers::NumberFormat,
+    phonetic, sanitize_spaces_chars, sanitize_spaces_iter, Normalizer,
+};
+use polars::prelude::*;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum ExprError {
+    #[error("column `{0}` must be of type String but is `{1}`")]
+    NotString(String, DataType),
+}
+
+impl From<ExprError> for PolarsError {
+    fn from(err: ExprError) -> Self {
+        match err {
+            ExprError::NotString(..) => PolarsError::SchemaMismatch(err.to_string().into()),
+        }
+    }
+}
+
+/// Extension to apply the `strings` normalizers to polars expressions
+///
+/// ```no_run
+/// use polars::prelude::*;
+/// use polars_sheet_reader::DipeExpr;
+///
+/// let key = col("Nombre").dipe().name_key().alias("Llave");
+/// ```
+pub trait DipeExpr {
+    fn dipe(self) -> DipeNameSpace;
+}
+
+impl DipeExpr for Expr {
+    fn dipe(self) -> DipeNameSpace {
+        DipeNameSpace(self)
+    }
+}
+
+/// Operations over `String` columns, nulls are kept as nulls. Applying them to columns of other
+/// types fails with [`ExprError::NotString`]
+pub struct DipeNameSpace(Expr);
+
+impl DipeNameSpace {
+    /// Keep a single space between words, see [`strings::sanitize_spaces`]
+    pub fn sanitize_spaces(self) -> Expr {
+        self.map_str(|value, buf| buf.extend(sanitize_spaces_chars(value)))
+    }
+
+    /// Repair text encoded as UTF-8 but decoded as Latin-1 or Windows-1252, see
+    /// [`strings::fix_mojibake`]
+    pub fn fix_mojibake(self) -> Expr {
+        self.map_str(|value, buf| buf.push_str(&fix_mojibake(value)))
+    }
+
+    /// Remove accents without changing the case
+    pub fn strip_accents(self) -> Expr {
+        self.normalize(Normalizer::new().fold_case(false))
+    }
+
+    /// Apply `normalizer` to each value
+    pub fn normalize(self, normalizer: Normalizer) -> Expr {
+        self.map_str(move |value, buf| normalizer.normalize_into(value, buf))
+    }
+
+    /// Canonical key of the personal name, see [`FullName::key`]
+    pub fn name_key(self) -> Expr {
+        self.map_str(|value, buf| buf.push_str(&FullName::parse(value).key()))
+    }
+
+    /// Key with the first `n` chars of the first given name and of each surname, see
+    /// [`FullName::initials_key`]
+    pub fn initials_key(self, n: usize) -> Expr {
+        self.map_str(move |value, buf| buf.push_str(&FullName::parse(value).initials_key(n)))
+    }
+
+    /// Spanish phonetic code of the words, see [`strings::phonetic`]
+    pub fn phonetic(self) -> Expr {
+        self.map_str(|value, buf| buf.extend(phonetic(sanitize_spaces_iter(value))))
+    }
+
+    /// Normalized CURP, null if it is not valid, see [`Curp::parse`]
+    pub fn curp(self) -> Expr {
+        self.filter_map_str(|value| Curp::parse(value).ok().map(|curp| curp.to_string()))
+    }
+
+    /// Birthdate of the CURP as a `Date`, null if it is not valid
+    pub fn curp_birthdate(self) -> Expr {
+        self.0.map(
+            |s| {
+                let ca = str_chunked(&s)?;
+                let days: Int32Chunked = ca
+                    .into_iter()
+                    .map(|value| {
+                        let curp = Curp::parse(value?).ok()?;
+                        Some(curp.birthdate().days_since_epoch())
+                    })
+                    .collect();
+
+                Ok(Some(days.with_name(ca.name()).into_date().into_series()))
+            },
+            GetOutput::from_type(DataType::Date),
+        )
+    }
+
+    /// Sex of the CURP as `"H"`, `"M"` or `"X"`, null if it is not valid
+    pub fn curp_sex(self) -> Expr {
+        self.filter_map_str(|value| {
+            let sex = match Curp::parse(value).ok()?.sex() {
+                Sex::Male => "H",
+                Sex::Female => "M",
+                Sex::NonBinary => "X",
+            };
+            Some(sex.to_string())
+        })
+    }
+
+    /// Normalized RFC, null if it is not valid, see [`Rfc::parse`]
+    pub fn rfc(self) -> Expr {
+        self.filter_map_str(|value| Rfc::parse(value).ok().map(|rfc| rfc.to_string()))
+    }
+
+    /// Normalized CVU, null if it is not valid, see [`Cvu::parse`]
+    pub fn cvu(self) -> Expr {
+        self.filter_map_str(|value| Cvu::parse(value).ok().map(|cvu| cvu.to_string()))
+    }
+
+    /// Number written as text with `format`, as `Float64`. Null if the value is empty, a
+    /// spreadsheet error like `"#DIV/0!"` or not a number, see [`NumberFormat::parse`]
+    pub fn parse_number(self, format: NumberFormat) -> Expr {
+        self.0.map(
+            move |s| {
+                let ca = str_chunked(&s)?;
+                let res: Float64Chunked = ca
+                    .into_iter()
+                    .map(|value| Some(format.parse(value?).ok()?.as_f64()))
+                    .collect();
+
+                Ok(Some(res.with_name(ca.name()).into_series()))
+            },
+            GetOutput::from_type(DataType::Float64),
+        )
+    }
+
+    fn map_str<F>(self, f: F) -> Expr
+    where
+        F: Fn(&str, &mut String) + Copy + Send + Sync + 'static,
+    {
+        self.0.map(
+            move |s| Ok(Some(str_chunked(&s)?.apply_to_buffer(f).into_series())),
+            GetOutput::from_type(DataType::String),
+        )
+    }
+
+    fn filter_map_str<F>(self, f: F) -> Expr
+    where
+        F: Fn(&str) -> Option<String> + Copy + Send + Sync + 'static,
+    {
+        self.0.map(
+            move |s| {
+                let ca = str_chunked(&s)?;
+                let res: StringChunked = ca.into_iter().map(|value| value.and_then(f)).collect();
+
+                Ok(Some(res.with_name(ca.name()).into_series()))
+            },
+            GetOutput::from_type(DataType::String),
+        )
+    }
+}
+
+fn str_chunked(s: &Series) -> PolarsResult<&StringChunked> {
+    s.str()
+        .map_err(|_| ExprError::NotString(s.name().to_string(), s.dtype().clone()).into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn apply(values: &[Option<&str>], f: impl Fn(DipeNameSpace) -> Expr) -> Vec<Option<String>> {
+        let df = df!("Valor" => values).expect("valid frame");
+        let res = df
+            .lazy()
+            .select([f(col("Valor").dipe())])
+            .collect()
+            .expect("string column");
+
+        let values = res
+            .column("Valor")
+            .expect("same name")
+            .str()
+            .expect("strings");
+        values.into_iter().map(|v| v.map(str::to_string)).collect()
+    }
+
+    #[test]
+    fn text() {
+        let values = [Some("  Rodríguez   Pérez,  Juan Pablo "), None, Some("")];
+
+        assert_eq!(
+            apply(&values, DipeNameSpace::sanitize_spaces),
+            [
+                Some("Rodríguez Pérez, Juan Pablo".to_string()),
+                None,
+                Some(String::new())
+            ]
+        );
+        assert_eq!(
+            apply(&values, DipeNameSpace::strip_accents),
+            [
+                Some("  Rodriguez   Perez,  Juan Pablo ".to_string()),
+                None,
+                Some(String::new())
+            ]
+        );
+        assert_eq!(
+            apply(&[Some("InstituciÃ³n"), None], DipeNameSpace::fix_mojibake),
+            [Some("Institución".to_string()), None]
+        );
+        assert_eq!(
+            apply(&values, |e| e.normalize(Normalizer::new())),
+            [
+                Some("  rodriguez   perez,  juan pablo ".to_string()),
+                None,
+                Some(String::new())
+            ]
+        );
+    }
+
+    #[test]
+    fn names() {
+        let values = [Some("Rodríguez Pérez, Juan Pablo"), None];
+
+        assert_eq!(
+            apply(&values, DipeNameSpace::name_key),
+            [Some("juan pablo rodriguez perez".to_string()), None]
+        );
+        assert_eq!(
+            apply(&values, |e| e.initials_key(2)),
+            [Some("ju ro pe".to_string()), None]
+        );
+        assert_eq!(
+            apply(&[Some("Velázquez"), None], DipeNameSpace::phonetic),
+            [Some("belaskes".to_string()), None]
+        );
+    }
+
+    #[test]
+    fn ids() {
+        let curps = [
+            Some("hegg-560427-mvzrrl04"),
+            Some("HEGG560427MVZRRL05"),
+            None,
+        ];
+
+        assert_eq!(
+            apply(&curps, DipeNameSpace::curp),
+            [Some("HEGG560427MVZRRL04".to_string()), None, None]
+        );
+        assert_eq!(
+            apply(&curps, DipeNameSpace::curp_sex),
+            [Some("M".to_string()), None, None]
+        );
+        assert_eq!(
+            apply(
+                &[Some(" gode561231gr8"), Some("GODE561231GR9"), None],
+                DipeNameSpace::rfc
+            ),
+            [Some("GODE561231GR8".to_string()), None, None]
+        );
+        assert_eq!(
+            apply(
+                &[Some("00123456"), Some("12A456"), None],
+                DipeNameSpace::cvu
+            ),
+            [Some("123456".to_string()), None, None]
+        );
+    }
+
+    #[test]
+    fn curp_birthdate() {
+        let df = df!("CURP" => [Some("HEGG560427MVZRRL04"), Some("HEGG560427MVZRRL05"), None])
+            .expect("valid frame");
+        let res = df
+            .lazy()
+            .select([col("CURP").dipe().curp_birthdate()])
+            .collect()
+            .expect("string column");
+
+        let dates = res.column("CURP").expect("same name");
+        assert_eq!(dates.dtype(), &DataType::Date);
+        // 1956-04-27 in days since 1970-01-01
+        let days = dates.date().expect("dates").into_iter().collect::<Vec<_>>();
+        assert_eq!(days, [Some(-4997), None, None]);
+    }
+
+    #[test]
+    fn parse_number() {
+        let values = [
+            Some("$1.234,5"),
+            Some("-12%"),
+            Some("#DIV/0!"),
+            Some(""),
+            Some("1,234.5"),
+            None,
+        ];
+        let df = df!("Monto" => values).expect("valid frame");
+        let res = df
+            .lazy()
+            .select([col("Monto")
+                .dipe()
+                .parse_number(NumberFormat::comma_decimal())])
+            .collect()
+            .expect("string column");
+
+        let numbers = res
+            .column("Monto")
+            .expect("same name")
+            .f64()
+            .expect("floats");
+        assert_eq!(
+            numbers.into_iter().collect::<Vec<_>>(),
+            [Some(1234.5), Some(-0.12), None, None, None, None]
+        );
+    }
+
+    #[test]
+    fn not_string() {
+        let df = df!("Id" => [1, 2]).expect("valid frame");
+        let res = df.lazy().select([col("Id").dipe().name_key()]).collect();
+
+        let Err(PolarsError::SchemaMismatch(msg)) = res else {
+            panic!("expected a schema mismatch, got {res:?}");
+        };
+        assert!(msg.contains("`Id`"), "{msg}");
+    }
+}
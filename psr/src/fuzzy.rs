@@ -0,0 +1,354 @@
+use ::strings::{get_words, names::FullName, phonetic_key, similarity::edit_ratio};
+use polars::prelude::*;
+use std::collections::{HashMap, HashSet};
+
+/// Name of the column with the similarity (`0..=1`) of each match
+pub const SIMILARITY: &str = "similarity";
+/// Name of the column with the rank of each match for the left row, starting at 1
+pub const MATCH_RANK: &str = "match_rank";
+/// Name of the column that flags left rows with more than one match of nearly the best similarity
+pub const AMBIGUOUS: &str = "ambiguous";
+
+/// Options for [`fuzzy_join`]
+#[derive(Debug, Clone, Copy)]
+pub struct FuzzyJoinOptions {
+    /// Minimum similarity (`0..=1`) for two names to match
+    pub threshold: f64,
+    /// Maximum number of matches kept for each left row
+    pub max_matches: usize,
+    /// A left row is ambiguous if its second best match is within this margin of the best one
+    pub ambiguity_margin: f64,
+    /// Only names that share the first `block_chars` chars of any word are compared
+    pub block_chars: usize,
+    /// Also compare names that share the first `block_chars` of the Spanish phonetic code of any
+    /// word, so `"Ximena"` is compared with `"Jimena"`
+    pub phonetic_blocks: bool,
+}
+
+impl Default for FuzzyJoinOptions {
+    fn default() -> Self {
+        Self {
+            threshold: 0.85,
+            max_matches: 1,
+            ambiguity_margin: 0.02,
+            block_chars: 2,
+            phonetic_blocks: true,
+        }
+    }
+}
+
+/// Left join `left` with `right` matching the personal names in `left_on` and `right_on` even if
+/// they differ slightly.
+///
+/// Names are compared by their [`FullName::key`] with a normalized edit distance. Every row of
+/// `left` appears at least once; rows without a match have nulls in the columns of `right`. The
+/// columns of `right` that collide with the ones of `left` get the `_right` suffix, and the
+/// [`SIMILARITY`], [`MATCH_RANK`] and [`AMBIGUOUS`] columns are added.
+///
+/// # Errors
+///
+/// This function will return an error if the key columns do not exist or are not strings, or if
+/// `left` already has a column named like a renamed or added one, like `Nombre_right` or
+/// [`SIMILARITY`]
+pub fn fuzzy_join(
+    left: &DataFrame,
+    right: &DataFrame,
+    left_on: &str,
+    right_on: &str,
+    options: &FuzzyJoinOptions,
+) -> PolarsResult<DataFrame> {
+    let left_keys = name_keys(left.column(left_on)?.str()?);
+    let right_keys = name_keys(right.column(right_on)?.str()?);
+
+    let mut blocks: HashMap<String, Vec<usize>> = HashMap::new();
+    for (i, key) in right_keys.iter().enumerate() {
+        for block in block_keys(key, options) {
+            blocks.entry(block).or_default().push(i);
+        }
+    }
+
+    let mut left_idx = Vec::with_capacity(left_keys.len());
+    let mut right_idx = Vec::with_capacity(left_keys.len());
+    let mut similarities = Vec::with_capacity(left_keys.len());
+    let mut ranks = Vec::with_capacity(left_keys.len());
+    let mut ambiguous = Vec::with_capacity(left_keys.len());
+
+    for (i, key) in left_keys.iter().enumerate() {
+        let candidates = block_keys(key, options)
+            .iter()
+            .filter_map(|block| blocks.get(block))
+            .flatten()
+            .copied()
+            .collect::<HashSet<_>>();
+
+        let mut matches = candidates
+            .into_iter()
+            .map(|j| (j, edit_ratio(key, &right_keys[j])))
+            .filter(|(_, score)| *score >= options.threshold)
+            .collect::<Vec<_>>();
+        // Ties are broken by row order so the result is deterministic
+        matches.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+
+        let is_ambiguous = matches
+            .get(1)
+            .is_some_and(|second| matches[0].1 - second.1 <= options.ambiguity_margin);
+
+        if matches.is_empty() {
+            left_idx.push(to_idx(i)?);
+            right_idx.push(None);
+            similarities.push(None);
+            ranks.push(None);
+            ambiguous.push(false);
+        }
+
+        for (rank, (j, score)) in (1u32..).zip(matches.into_iter().take(options.max_matches)) {
+            left_idx.push(to_idx(i)?);
+            right_idx.push(Some(to_idx(j)?));
+            similarities.push(Some(score));
+            ranks.push(Some(rank));
+            ambiguous.push(is_ambiguous);
+        }
+    }
+
+    let mut res = left.take(&IdxCa::from_vec("", left_idx))?;
+    let matched = right.take(&IdxCa::from_iter_options("", right_idx.into_iter()))?;
+
+    for series in matched.get_columns() {
+        let mut series = series.clone();
+        if res.get_column_names().contains(&series.name()) {
+            let name = format!("{}_right", series.name());
+            series.rename(&name);
+        }
+        add_column(&mut res, series)?;
+    }
+
+    add_column(&mut res, Series::new(SIMILARITY, similarities))?;
+    add_column(&mut res, Series::new(MATCH_RANK, ranks))?;
+    add_column(&mut res, Series::new(AMBIGUOUS, ambiguous))?;
+
+    Ok(res)
+}
+
+/// Add `series` to `df`, failing instead of replacing a column with the same name
+fn add_column(df: &mut DataFrame, series: Series) -> PolarsResult<()> {
+    if df.get_column_names().contains(&series.name()) {
+        return Err(PolarsError::Duplicate(
+            format!(
+                "column `{}` already exists in the fuzzy join",
+                series.name()
+            )
+            .into(),
+        ));
+    }
+    df.with_column(series)?;
+
+    Ok(())
+}
+
+fn name_keys(names: &StringChunked) -> Vec<String> {
+    names
+        .into_iter()
+        .map(|name| {
+            name.map(|name| FullName::parse(name).key())
+                .unwrap_or_default()
+        })
+        .collect()
+}
+
+/// The first chars of every word in `key` (and of its phonetic code), so names with reordered words
+/// still share a block
+fn block_keys(key: &str, options: &FuzzyJoinOptions) -> Vec<String> {
+    let prefix = |word: &str| word.chars().take(options.block_chars).collect::<String>();
+    let mut blocks = get_words(key).map(|word| prefix(&word)).collect::<Vec<_>>();
+
+    if options.phonetic_blocks {
+        blocks.extend(get_words(&phonetic_key(key)).map(|word| prefix(&word)));
+    }
+
+    blocks
+}
+
+fn to_idx(i: usize) -> PolarsResult<IdxSize> {
+    IdxSize::try_from(i)
+        .map_err(|_| PolarsError::ComputeError(format!("row {i} does not fit in an index").into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn left() -> DataFrame {
+        df!(
+            "Id" => [1, 2, 3, 4],
+            "Nombre" => [Some("Juan Pérez"), Some("Ana Paz"), None, Some("Maria Lopez")],
+        )
+        .expect("valid frame")
+    }
+
+    fn right() -> DataFrame {
+        df!(
+            "Nombre" => [Some("Juan Peres"), Some("Eva Sol"), None, Some("María López"), Some("Mario Lopez")],
+            "Nivel" => ["I", "II", "III", "C", "E"],
+        )
+        .expect("valid frame")
+    }
+
+    fn join(options: &FuzzyJoinOptions) -> DataFrame {
+        fuzzy_join(&left(), &right(), "Nombre", "Nombre", options).expect("string keys")
+    }
+
+    fn column<'a>(df: &'a DataFrame, name: &str) -> &'a Series {
+        df.column(name).expect("column")
+    }
+
+    fn strs<'a>(df: &'a DataFrame, name: &str) -> Vec<Option<&'a str>> {
+        column(df, name)
+            .str()
+            .expect("strings")
+            .into_iter()
+            .collect()
+    }
+
+    fn ranks(df: &DataFrame) -> Vec<Option<u32>> {
+        column(df, MATCH_RANK)
+            .u32()
+            .expect("ranks")
+            .into_iter()
+            .collect()
+    }
+
+    fn ambiguous(df: &DataFrame) -> Vec<Option<bool>> {
+        column(df, AMBIGUOUS)
+            .bool()
+            .expect("flags")
+            .into_iter()
+            .collect()
+    }
+
+    fn similarities(df: &DataFrame) -> Vec<Option<f64>> {
+        let ca = column(df, SIMILARITY).f64().expect("similarities");
+        ca.into_iter()
+            .map(|s| s.map(|s| (s * 1000.).round() / 1000.))
+            .collect()
+    }
+
+    #[test]
+    fn keeps_every_left_row() {
+        let res = join(&FuzzyJoinOptions::default());
+
+        assert_eq!(
+            res.get_column_names(),
+            [
+                "Id",
+                "Nombre",
+                "Nombre_right",
+                "Nivel",
+                SIMILARITY,
+                MATCH_RANK,
+                AMBIGUOUS
+            ]
+        );
+        assert_eq!(
+            strs(&res, "Nombre_right"),
+            [Some("Juan Peres"), None, None, Some("María López")]
+        );
+        assert_eq!(strs(&res, "Nivel"), [Some("I"), None, None, Some("C")]);
+        assert_eq!(similarities(&res), [Some(0.9), None, None, Some(1.)]);
+        assert_eq!(ranks(&res), [Some(1), None, None, Some(1)]);
+        assert_eq!(ambiguous(&res), [Some(false); 4]);
+    }
+
+    #[test]
+    fn threshold() {
+        let res = join(&FuzzyJoinOptions {
+            threshold: 0.95,
+            ..Default::default()
+        });
+
+        assert_eq!(strs(&res, "Nivel"), [None, None, None, Some("C")]);
+    }
+
+    #[test]
+    fn blocking() {
+        let options = FuzzyJoinOptions {
+            threshold: 0.,
+            phonetic_blocks: false,
+            ..Default::default()
+        };
+        // "Ana Paz" shares no word prefix with any name on the right
+        assert_eq!(strs(&join(&options), "Nivel")[1], None);
+
+        // Without blocks every name is compared, but null names still never match
+        let res = join(&FuzzyJoinOptions {
+            block_chars: 0,
+            ..options
+        });
+        assert!(strs(&res, "Nivel")[1].is_some());
+        assert_eq!(strs(&res, "Nivel")[2], None);
+        assert_eq!(column(&res, SIMILARITY).null_count(), 1);
+    }
+
+    #[test]
+    fn ranks_matches_by_similarity() {
+        let res = join(&FuzzyJoinOptions {
+            max_matches: 3,
+            ..Default::default()
+        });
+
+        assert_eq!(column(&res, "Id").i32().expect("ids").get(4), Some(4));
+        assert_eq!(
+            strs(&res, "Nombre_right")[3..],
+            [Some("María López"), Some("Mario Lopez")]
+        );
+        assert_eq!(ranks(&res)[3..], [Some(1), Some(2)]);
+        assert_eq!(similarities(&res)[3..], [Some(1.), Some(0.909)]);
+        assert_eq!(ambiguous(&res)[3..], [Some(false); 2]);
+
+        let res = join(&FuzzyJoinOptions {
+            max_matches: 3,
+            ambiguity_margin: 0.1,
+            ..Default::default()
+        });
+        assert_eq!(ambiguous(&res)[3..], [Some(true); 2]);
+    }
+
+    #[test]
+    fn flags_ties_as_ambiguous() {
+        let left = df!("Nombre" => ["Juan Perez"]).expect("valid frame");
+        let right = df!("Nombre" => ["Juan Perex", "Juan Peres"]).expect("valid frame");
+        let options = FuzzyJoinOptions::default();
+
+        let res = fuzzy_join(&left, &right, "Nombre", "Nombre", &options).expect("string keys");
+
+        // Ties keep the order of `right`
+        assert_eq!(strs(&res, "Nombre_right"), [Some("Juan Perex")]);
+        assert_eq!(ambiguous(&res), [Some(true)]);
+    }
+
+    #[test]
+    fn key_must_be_string() {
+        let options = FuzzyJoinOptions::default();
+        let err = fuzzy_join(&left(), &right(), "Id", "Nombre", &options);
+
+        assert!(matches!(err, Err(PolarsError::SchemaMismatch(_))));
+    }
+
+    #[test]
+    fn never_replaces_columns() {
+        let options = FuzzyJoinOptions::default();
+        let taken = |name: &str| {
+            let mut left = left();
+            left.with_column(Series::new(name, [0, 0, 0, 0]))
+                .expect("same height");
+            fuzzy_join(&left, &right(), "Nombre", "Nombre", &options)
+        };
+
+        for name in ["Nombre_right", SIMILARITY, AMBIGUOUS] {
+            assert!(
+                matches!(taken(name), Err(PolarsError::Duplicate(ref msg)) if msg.contains(name)),
+                "{name}"
+            );
+        }
+        assert!(taken("Nivel_right").is_ok());
+    }
+}
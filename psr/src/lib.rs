@@ -3,10 +3,13 @@
 #![deny(rust_2018_idioms, unsafe_code)]
 #![deny(clippy::unwrap_used)]
 
-use ::strings::sanitize_spaces;
+use ::strings::{fix_mojibake, safe_names::file_path, sanitize_spaces};
 use calamine::{open_workbook, Data, DataType as _, Reader};
 use polars::prelude::*;
+use polars_excel_writer::PolarsXlsxWriter;
+use rust_xlsxwriter::Format;
 use std::{
+    borrow::Cow,
     collections::HashMap,
     fs::File,
     io::BufReader,
@@ -15,6 +18,12 @@ use std::{
 };
 use thiserror::Error;
 
+pub use expr::{DipeExpr, DipeNameSpace, ExprError};
+pub use fuzzy::{fuzzy_join, FuzzyJoinOptions};
+
+mod expr;
+pub mod fuzzy;
+
 const INT: u8 = 0b1000_0000;
 const FLOAT: u8 = 0b0100_0000;
 const STRING: u8 = 0b0010_0000;
@@ -101,7 +110,7 @@ pub fn read_set_from_sheet_at<R: Reader<BufReader<File>>>(
     };
     let range = excel
         .worksheet_range(sheet)
-        .map_err(|e| ReaderError::OpenWorksheet(sheet.to_string(), format!("{e:?}")))?;
+        .map_err(|e| ReaderError::OpenWorksheet(sheet.clone(), format!("{e:?}")))?;
 
     let mut row = range.rows().map(|c| {
         if let [k, v, ..] = c {
@@ -138,10 +147,50 @@ pub enum ReaderError {
     NoHeaders(String),
     #[error("failed to add column `{0}` with `{1}`")]
     AddColumn(String, String),
+    #[error("failed to read `{1}` as a decimal in column `{0}`")]
+    ParseDecimal(String, String),
+    #[error("failed to write workbook at `{0:?}` with `{1}`")]
+    WriteWorkbook(PathBuf, String),
+    #[error("failed to read CSV file at `{0:?}` with `{1}`")]
+    ReadCsv(PathBuf, String),
 }
 
 pub type ReaderResult<T> = std::result::Result<T, ReaderError>;
 
+/// Options that change how the values of a sheet are read into a `DataFrame`
+#[derive(Debug, Clone, Default)]
+pub struct ReadOptions {
+    decimals: HashMap<String, usize>,
+    repair_mojibake: bool,
+}
+
+impl ReadOptions {
+    /// Read the column named `column` as a fixed-precision `Decimal` with `scale` digits after the
+    /// decimal point instead of `Float64`, so sums of money are exact
+    #[must_use]
+    pub fn with_decimal(mut self, column: impl Into<String>, scale: usize) -> Self {
+        self.decimals.insert(column.into(), scale);
+        self
+    }
+
+    /// Repair the headers and string cells that were encoded as UTF-8 but decoded as Latin-1 or
+    /// Windows-1252, like `"InstituciÃ³n"`, see [`strings::fix_mojibake`]. Columns configured with
+    /// [`ReadOptions::with_decimal`] are matched by their repaired header
+    #[must_use]
+    pub fn repair_mojibake(mut self, repair: bool) -> Self {
+        self.repair_mojibake = repair;
+        self
+    }
+
+    fn header(&self, header: String) -> String {
+        if self.repair_mojibake {
+            fix_mojibake(&header).into_owned()
+        } else {
+            header
+        }
+    }
+}
+
 /// Read all sheets from a path into a collection of `DataFrame`
 ///
 /// # Errors
@@ -149,6 +198,23 @@ pub type ReaderResult<T> = std::result::Result<T, ReaderError>;
 /// This function will return an error if theres an error opening sheets, workbooks or while
 /// adding columns into dataframes
 pub fn read_sheets<R, P>(path: P) -> ReaderResult<PlIndexMap<String, DataFrame>>
+where
+    R: Reader<BufReader<File>>,
+    P: AsRef<Path>,
+{
+    read_sheets_with::<R, P>(path, &ReadOptions::default())
+}
+
+/// Read all sheets from a path into a collection of `DataFrame` using `options`
+///
+/// # Errors
+///
+/// This function will return an error if theres an error opening sheets, workbooks or while
+/// adding columns into dataframes
+pub fn read_sheets_with<R, P>(
+    path: P,
+    options: &ReadOptions,
+) -> ReaderResult<PlIndexMap<String, DataFrame>>
 where
     R: Reader<BufReader<File>>,
     P: AsRef<Path>,
@@ -159,7 +225,7 @@ where
     let sheets = excel.sheet_names();
 
     for sheet in sheets {
-        let df = read_sheet_from_sheets(&mut excel, &sheet)?;
+        let df = read_sheet_from_sheets_with(&mut excel, &sheet, options)?;
         res.insert(sheet, df);
     }
 
@@ -173,6 +239,20 @@ where
 /// This function will return an error if theres an error opening sheets, workbooks or while
 /// adding columns into dataframes
 pub fn read_sheet<P, R>(path: P, sheet: &str) -> ReaderResult<DataFrame>
+where
+    R: Reader<BufReader<File>>,
+    P: AsRef<Path>,
+{
+    read_sheet_with::<P, R>(path, sheet, &ReadOptions::default())
+}
+
+/// Read a single sheet named `sheet` from the path `path` using `options`
+///
+/// # Errors
+///
+/// This function will return an error if theres an error opening sheets, workbooks or while
+/// adding columns into dataframes
+pub fn read_sheet_with<P, R>(path: P, sheet: &str, options: &ReadOptions) -> ReaderResult<DataFrame>
 where
     R: Reader<BufReader<File>>,
     P: AsRef<Path>,
@@ -180,7 +260,7 @@ where
     let mut excel: R = open_workbook(path.as_ref())
         .map_err(|e| ReaderError::OpenWorkbook(path.as_ref().to_path_buf(), format!("{e:?}")))?;
 
-    read_sheet_from_sheets(&mut excel, sheet)
+    read_sheet_from_sheets_with(&mut excel, sheet, options)
 }
 
 /// Read the nth sheet from the path `path`
@@ -190,6 +270,24 @@ where
 /// This function will return an error if theres an error opening sheets, workbooks or while
 /// adding columns into dataframes
 pub fn read_sheet_nth<P, R>(path: P, nth: usize) -> ReaderResult<DataFrame>
+where
+    R: Reader<BufReader<File>>,
+    P: AsRef<Path>,
+{
+    read_sheet_nth_with::<P, R>(path, nth, &ReadOptions::default())
+}
+
+/// Read the nth sheet from the path `path` using `options`
+///
+/// # Errors
+///
+/// This function will return an error if theres an error opening sheets, workbooks or while
+/// adding columns into dataframes
+pub fn read_sheet_nth_with<P, R>(
+    path: P,
+    nth: usize,
+    options: &ReadOptions,
+) -> ReaderResult<DataFrame>
 where
     R: Reader<BufReader<File>>,
     P: AsRef<Path>,
@@ -207,7 +305,7 @@ where
         )
     })?;
 
-    read_sheet_from_sheets(&mut excel, sheet)
+    read_sheet_from_sheets_with(&mut excel, sheet, options)
 }
 
 /// Reads a single sheet into a dataframe, detecting de data type for each column
@@ -224,6 +322,26 @@ where
 pub fn read_sheet_from_sheets<R: Reader<BufReader<File>>>(
     excel: &mut R,
     sheet: &str,
+) -> ReaderResult<DataFrame> {
+    read_sheet_from_sheets_with(excel, sheet, &ReadOptions::default())
+}
+
+/// Reads a single sheet into a dataframe using `options`, detecting de data type for each column
+/// that is not configured otherwise
+///
+/// # Errors
+///
+/// This function will return an error if there is a problem adding columns because of different sizes,
+///  if the sheet does not have headers, if a decimal column has values that are not numbers, etc.
+///
+/// # Panics
+///
+/// The function panics if values can not be generalized to a single type, this is a bug and must be
+/// reported
+pub fn read_sheet_from_sheets_with<R: Reader<BufReader<File>>>(
+    excel: &mut R,
+    sheet: &str,
+    options: &ReadOptions,
 ) -> ReaderResult<DataFrame> {
     let mut df = DataFrame::default();
     let range = excel
@@ -239,17 +357,25 @@ pub fn read_sheet_from_sheets<R: Reader<BufReader<File>>>(
     };
 
     let headers = header_row.iter().map(|d| {
-        if let Data::String(s) = d {
-            s.to_string()
+        let header = if let Data::String(s) = d {
+            s.clone()
         } else {
             d.to_string()
-        }
+        };
+        options.header(header)
     });
 
     for (n_col, header) in headers.enumerate() {
         let mut flags = EMPTY;
         let values = rows.clone().map(|row| &row[n_col]);
 
+        if let Some(&scale) = options.decimals.get(&header) {
+            let series = decimal_series(&header, values.map(decimal_from_data), scale)?;
+            df.with_column(series)
+                .map_err(|e| ReaderError::AddColumn(header, format!("{e:?}")))?;
+            continue;
+        }
+
         for value in values.clone() {
             flags |= match value {
                 Data::Int(_) => INT,
@@ -297,6 +423,14 @@ pub fn read_sheet_from_sheets<R: Reader<BufReader<File>>>(
             );
         }
 
+        if options.repair_mojibake {
+            for value in vec_string.iter_mut().flatten() {
+                if let Cow::Owned(fixed) = fix_mojibake(value) {
+                    *value = fixed;
+                }
+            }
+        }
+
         assert_eq!(
             u8::from(vec_date.is_empty().not())
                 + u8::from(vec_boolean.is_empty().not())
@@ -322,6 +456,80 @@ pub fn read_sheet_from_sheets<R: Reader<BufReader<File>>>(
     Ok(df)
 }
 
+/// Read a CSV file with headers into a `DataFrame`, inferring the type of each column from all of
+/// its rows
+///
+/// # Errors
+///
+/// This function will return an error if the file can not be opened or parsed
+pub fn read_csv(path: impl AsRef<Path>) -> ReaderResult<DataFrame> {
+    read_csv_with(path, &ReadOptions::default())
+}
+
+/// Read a CSV file with headers into a `DataFrame` using `options`, inferring the type of each
+/// column from all of its rows that is not configured otherwise
+///
+/// # Errors
+///
+/// This function will return an error if the file can not be opened or parsed, or if a decimal
+/// column has values that are not numbers
+pub fn read_csv_with(path: impl AsRef<Path>, options: &ReadOptions) -> ReaderResult<DataFrame> {
+    let csv_err =
+        |e: PolarsError| ReaderError::ReadCsv(path.as_ref().to_path_buf(), format!("{e:?}"));
+
+    // Decimal columns are read as text so their digits are not rounded by a float
+    let raw = CsvReader::from_path(path.as_ref())
+        .and_then(|reader| reader.has_header(true).infer_schema(Some(0)).finish())
+        .map_err(csv_err)?;
+    let text_dtypes = raw
+        .get_column_names()
+        .into_iter()
+        .filter(|name| {
+            options
+                .decimals
+                .contains_key(&options.header((*name).to_string()))
+        })
+        .map(|name| Field::new(name, DataType::String))
+        .collect::<Schema>();
+
+    let df = CsvReader::from_path(path.as_ref())
+        .and_then(|reader| {
+            reader
+                .has_header(true)
+                .infer_schema(None)
+                .with_dtypes(Some(Arc::new(text_dtypes)))
+                .finish()
+        })
+        .map_err(csv_err)?;
+
+    let columns = df
+        .get_columns()
+        .iter()
+        .map(|s| {
+            let name = options.header(s.name().to_string());
+            if let Some(&scale) = options.decimals.get(&name) {
+                let values = s.str().map_err(csv_err)?;
+                let values = values
+                    .into_iter()
+                    .map(|value| Ok(value.and_then(decimal_text)));
+                return decimal_series(&name, values, scale);
+            }
+
+            Ok(match s.str() {
+                Ok(ca) if options.repair_mojibake => ca
+                    .into_iter()
+                    .map(|value| value.map(fix_mojibake))
+                    .collect::<StringChunked>()
+                    .with_name(&name)
+                    .into_series(),
+                _ => s.clone().with_name(&name),
+            })
+        })
+        .collect::<ReaderResult<Vec<_>>>()?;
+
+    DataFrame::new(columns).map_err(csv_err)
+}
+
 fn populate_vectors(
     value: &Data,
     dtype: &DataType,
@@ -352,3 +560,309 @@ fn populate_vectors(
         },
     }
 }
+
+/// Text of a value to read as a decimal, None if it is empty. Returns the raw text of the value if
+/// it can not be read as a number
+type DecimalText = Result<Option<String>, String>;
+
+/// Build a `Decimal` series with `scale` digits after the decimal point from the texts of `values`
+fn decimal_series(
+    header: &str,
+    values: impl Iterator<Item = DecimalText>,
+    scale: usize,
+) -> ReaderResult<Series> {
+    let values = values
+        .map(|value| match value {
+            Ok(Some(text)) => parse_scaled(&text, scale).map(Some).ok_or(text),
+            Ok(None) => Ok(None),
+            Err(raw) => Err(raw),
+        })
+        .map(|value| value.map_err(|raw| ReaderError::ParseDecimal(header.to_string(), raw)))
+        .collect::<ReaderResult<Vec<_>>>()?;
+
+    Ok(Int128Chunked::from_iter_options(header, values.into_iter())
+        .into_decimal_unchecked(None, scale)
+        .into_series())
+}
+
+/// Text of a cell to read as a decimal
+fn decimal_from_data(value: &Data) -> DecimalText {
+    match value {
+        Data::Empty => Ok(None),
+        Data::Int(v) => Ok(Some(v.to_string())),
+        // The shortest text that reads back as the same float, so `0.1` is not `0.1000000000000000055`
+        Data::Float(v) => Ok(Some(v.to_string())),
+        Data::String(v) => Ok(decimal_text(v)),
+        other => Err(other.to_string()),
+    }
+}
+
+/// Text of a CSV or string cell to read as a decimal, without currency signs nor thousands
+/// separators. None if it is empty
+fn decimal_text(value: &str) -> Option<String> {
+    let text = value.replace(['$', ',', ' '], "");
+    (!text.is_empty()).then_some(text)
+}
+
+/// Parse a plain decimal number like `-1234.567` into an integer scaled by `10^scale`, rounding
+/// half away from zero the digits that do not fit in `scale`
+fn parse_scaled(text: &str, scale: usize) -> Option<i128> {
+    let (negative, text) = match text.strip_prefix('-') {
+        Some(text) => (true, text),
+        None => (false, text),
+    };
+    let (int, frac) = text.split_once('.').unwrap_or((text, ""));
+
+    if (int.is_empty() && frac.is_empty())
+        || !int.bytes().chain(frac.bytes()).all(|b| b.is_ascii_digit())
+    {
+        return None;
+    }
+
+    let kept = frac.get(..scale).unwrap_or(frac);
+    let round_up = frac.as_bytes().get(scale).is_some_and(|&d| d >= b'5');
+    let digits = format!("{int}{kept:0<scale$}");
+
+    let magnitude = if digits.is_empty() {
+        0
+    } else {
+        digits.parse::<i128>().ok()?
+    };
+    let magnitude = magnitude.checked_add(i128::from(round_up))?;
+
+    Some(if negative { -magnitude } else { magnitude })
+}
+
+/// Options that change how a `DataFrame` is written by [`write_xlsx_with`]
+#[derive(Debug, Clone, Default)]
+pub struct WriteOptions {
+    autofit: bool,
+    formats: HashMap<String, String>,
+}
+
+impl WriteOptions {
+    /// Fit the width of the columns to their contents
+    #[must_use]
+    pub fn autofit(mut self, autofit: bool) -> Self {
+        self.autofit = autofit;
+        self
+    }
+
+    /// Write the numeric column named `column` with the Excel number format `format`, like
+    /// `"0.00"` for a percentage stored as `12.5`. `Decimal` columns without one get a currency
+    /// format that keeps their scale
+    #[must_use]
+    pub fn with_format(mut self, column: impl Into<String>, format: impl Into<String>) -> Self {
+        self.formats.insert(column.into(), format.into());
+        self
+    }
+}
+
+/// Write `df` as an Excel workbook at `path`, with its file name made safe with
+/// [`strings::safe_names::file_name`]. `Decimal` columns are written as numbers with a currency
+/// format that keeps their scale
+///
+/// # Errors
+///
+/// This function will return an error if the dataframe has columns that can not be written to Excel
+/// or if there is a problem saving the workbook
+pub fn write_xlsx(df: &DataFrame, path: impl AsRef<Path>, autofit: bool) -> ReaderResult<()> {
+    write_xlsx_with(df, path, &WriteOptions::default().autofit(autofit))
+}
+
+/// Write `df` as an Excel workbook at `path` using `options`, with its file name made safe with
+/// [`strings::safe_names::file_name`]
+///
+/// # Errors
+///
+/// This function will return an error if the dataframe has columns that can not be written to
+/// Excel, if a column with a format is not numeric or if there is a problem saving the workbook
+pub fn write_xlsx_with(
+    df: &DataFrame,
+    path: impl AsRef<Path>,
+    options: &WriteOptions,
+) -> ReaderResult<()> {
+    let path = file_path(path.as_ref());
+    let write_err = |e: PolarsError| ReaderError::WriteWorkbook(path.clone(), format!("{e:?}"));
+    let xlsx_err =
+        |e: rust_xlsxwriter::XlsxError| ReaderError::WriteWorkbook(path.clone(), e.to_string());
+
+    let mut writer = PolarsXlsxWriter::new();
+    writer.set_autofit(options.autofit);
+
+    // Excel only stores doubles, the exact arithmetic must happen before writing
+    let mut df = df.clone();
+    let mut formatted = Vec::new();
+    for (n_col, s) in df.clone().get_columns().iter().enumerate() {
+        let format = match (options.formats.get(s.name()), s.dtype()) {
+            (Some(format), dtype)
+                if dtype.is_numeric() || matches!(dtype, DataType::Decimal(..)) =>
+            {
+                format.clone()
+            }
+            (None, DataType::Decimal(_, scale)) => currency_format(scale.unwrap_or_default()),
+            (Some(_), dtype) => {
+                return Err(ReaderError::WriteWorkbook(
+                    path.clone(),
+                    format!(
+                        "column `{}` of type {dtype} can not have a number format",
+                        s.name()
+                    ),
+                ))
+            }
+            (None, _) => continue,
+        };
+
+        let series = s.cast(&DataType::Float64).map_err(write_err)?;
+        df.with_column(series.clone()).map_err(write_err)?;
+        formatted.push((n_col, series, format));
+    }
+
+    writer.write_dataframe(&df).map_err(write_err)?;
+
+    // The writer gives every float the same format, so these columns are written again with theirs
+    let worksheet = writer.worksheet().map_err(write_err)?;
+    for (n_col, series, format) in formatted {
+        let format = Format::new().set_num_format(format);
+        let n_col = u16::try_from(n_col)
+            .map_err(|e| ReaderError::WriteWorkbook(path.clone(), e.to_string()))?;
+        for (n_row, value) in series.f64().map_err(write_err)?.into_iter().enumerate() {
+            if let (Some(value), Ok(n_row)) = (value, u32::try_from(n_row + 1)) {
+                worksheet
+                    .write_number_with_format(n_row, n_col, value, &format)
+                    .map_err(xlsx_err)?;
+            }
+        }
+    }
+    if options.autofit {
+        worksheet.autofit();
+    }
+
+    writer.save(&path).map_err(write_err)?;
+
+    Ok(())
+}
+
+/// Excel number format for an amount of money with `scale` decimal places
+fn currency_format(scale: usize) -> String {
+    if scale == 0 {
+        "$#,##0".to_string()
+    } else {
+        format!("$#,##0.{}", "0".repeat(scale))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scaled() {
+        assert_eq!(parse_scaled("1234.567", 2), Some(123_457));
+        assert_eq!(parse_scaled("1234.564", 2), Some(123_456));
+        assert_eq!(parse_scaled("9.995", 2), Some(1_000));
+        assert_eq!(parse_scaled("-1234.565", 2), Some(-123_457));
+        assert_eq!(parse_scaled("12", 3), Some(12_000));
+        assert_eq!(parse_scaled(".5", 0), Some(1));
+        assert_eq!(parse_scaled("7.", 1), Some(70));
+
+        for bad in ["", "-", ".", "abc", "1.2.3", "1e3", "--1", "1 000"] {
+            assert_eq!(parse_scaled(bad, 2), None, "{bad}");
+        }
+    }
+
+    #[test]
+    fn decimals() {
+        let cells = [
+            Data::Float(0.1),
+            Data::Float(2.675),
+            Data::Int(-3),
+            Data::String("$1,234.5".into()),
+            Data::Empty,
+            Data::String(" ".into()),
+        ];
+        let series = decimal_series("Monto", cells.iter().map(decimal_from_data), 2)
+            .expect("every cell is a number or empty");
+        assert_eq!(series.dtype(), &DataType::Decimal(None, Some(2)));
+
+        let ints: &Int128Chunked = series.decimal().expect("decimal");
+        assert_eq!(
+            ints.into_iter().collect::<Vec<_>>(),
+            [Some(10), Some(268), Some(-300), Some(123_450), None, None]
+        );
+
+        for bad in [Data::String("N/A".into()), Data::Bool(true)] {
+            let res = decimal_series("Monto", std::iter::once(&bad).map(decimal_from_data), 2);
+            assert!(
+                matches!(res, Err(ReaderError::ParseDecimal(ref column, _)) if column == "Monto"),
+                "{bad:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn csv_decimals() {
+        let path = std::env::temp_dir().join(format!("psr_decimales_{}.csv", std::process::id()));
+        std::fs::write(&path, "ID,Monto\n1,0.1\n2,0.2\n3,\n4,\"$1,000.05\"\n")
+            .expect("temporary file");
+
+        let options = ReadOptions::default().with_decimal("Monto", 2);
+        let df = read_csv_with(&path, &options);
+        let _ = std::fs::remove_file(&path);
+        let df = df.expect("valid CSV");
+
+        let monto = df.column("Monto").expect("column");
+        assert_eq!(monto.dtype(), &DataType::Decimal(None, Some(2)));
+        let ints: &Int128Chunked = monto.decimal().expect("decimal");
+        assert_eq!(
+            ints.into_iter().collect::<Vec<_>>(),
+            [Some(10), Some(20), None, Some(100_005)]
+        );
+        assert_eq!(df.column("ID").expect("column").dtype(), &DataType::Int64);
+    }
+
+    #[test]
+    fn formats() {
+        let path = std::env::temp_dir().join(format!("psr_formatos_{}.xlsx", std::process::id()));
+        let monto = Int128Chunked::from_iter_options("Monto", [Some(1_050), None].into_iter())
+            .into_decimal_unchecked(None, 2)
+            .into_series();
+        let df = df!(
+            "Porcentaje" => [12.5, 100.],
+            "Nombre" => ["Ana", "Eva"],
+        )
+        .and_then(|df| df.hstack(&[monto]))
+        .expect("valid frame");
+
+        let options = WriteOptions::default().with_format("Porcentaje", "0.00");
+        let written = write_xlsx_with(&df, &path, &options);
+        let read = read_sheet::<_, calamine::Xlsx<_>>(&path, "Sheet1");
+        let _ = std::fs::remove_file(&path);
+        written.expect("numeric columns");
+        let read = read.expect("written workbook");
+        assert_eq!(
+            read.column("Porcentaje")
+                .expect("column")
+                .f64()
+                .expect("floats")
+                .into_iter()
+                .collect::<Vec<_>>(),
+            [Some(12.5), Some(100.)]
+        );
+        assert_eq!(
+            read.column("Monto")
+                .expect("column")
+                .f64()
+                .expect("floats")
+                .into_iter()
+                .collect::<Vec<_>>(),
+            [Some(10.5), None]
+        );
+
+        let options = WriteOptions::default().with_format("Nombre", "0.00");
+        assert!(matches!(
+            write_xlsx_with(&df, &path, &options),
+            Err(ReaderError::WriteWorkbook(_, message)) if message.contains("`Nombre`")
+        ));
+    }
+}
@@ -1,7 +1,13 @@
-use ::strings::{chars_to_lower, rm_specials, space_join};
-use polars::{io::SerReader, lazy::frame::IntoLazy};
+use polars::lazy::frame::IntoLazy;
 use polars::{lazy::dsl::*, prelude::*};
-use polars_excel_writer::PolarsXlsxWriter;
+use polars_sheet_reader::{
+    fuzzy::{AMBIGUOUS, MATCH_RANK, SIMILARITY},
+    fuzzy_join, read_csv_with, write_xlsx, DipeExpr, FuzzyJoinOptions, ReadOptions,
+};
+
+/// Similitud mínima para aceptar un nombre que no está escrito igual en el padrón. "Maria Lopez" y
+/// "Mario Lopez" tienen 0.909, así que tiene que ser más alta que eso
+const SIMILITUD_MINIMA: f64 = 0.95;
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("Generando 2023...");
@@ -44,15 +50,10 @@ fn generar(
     nombre_bnf: &str,
     nombre: &str,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let df_bnf = polars::prelude::CsvReader::from_path(path_bnf)?
-        .has_header(true)
-        .infer_schema(None)
-        .finish()?;
-
-    let df_pfs = polars::prelude::CsvReader::from_path(path_profs)?
-        .has_header(true)
-        .infer_schema(None)
-        .finish()?;
+    // Las exportaciones de RH suelen traer UTF-8 leído como Windows-1252, como "InstituciÃ³n"
+    let options = ReadOptions::default().repair_mojibake(true);
+    let df_bnf = read_csv_with(path_bnf, &options)?;
+    let df_pfs = read_csv_with(path_profs, &options)?;
 
     let df_bnf = df_bnf
         .lazy()
@@ -61,28 +62,59 @@ fn generar(
             col("NOMBRE DEL INVESTIGADOR"),
             col("NIVEL"),
             col("FECHA FIN DE VIGENCIA").cast(DataType::UInt64),
+            col("NOMBRE DEL INVESTIGADOR")
+                .dipe()
+                .name_key()
+                .alias("SNombre"),
         ])
-        .with_columns([col("NOMBRE DEL INVESTIGADOR")
-            .map(name_id(), GetOutput::from_type(DataType::String))
-            .alias("SNombre")])
         .collect()?;
 
     let df_pfs = df_pfs
         .lazy()
-        .with_columns([col("Nombre")
-            .map(name_id(), GetOutput::from_type(DataType::String))
-            .alias("SNombre")])
+        .with_columns([col("Nombre").dipe().name_key().alias("SNombre")])
         .collect()?;
 
-    let df_pfs = df_pfs.join(
-        &df_bnf,
-        ["SNombre"],
-        ["SNombre"],
-        JoinArgs::new(JoinType::Left),
-    )?;
+    // Primero los nombres escritos igual que en el padrón
+    let exacto = col("SNombre")
+        .is_in(lit(df_bnf.column("SNombre")?.clone()))
+        .fill_null(lit(false));
+    let df_exactos = df_pfs
+        .clone()
+        .lazy()
+        .filter(exacto.clone())
+        .collect()?
+        .join(
+            &df_bnf,
+            ["SNombre"],
+            ["SNombre"],
+            JoinArgs::new(JoinType::Left),
+        )?
+        .lazy()
+        .with_columns([lit(1.).alias(SIMILARITY), lit(false).alias(AMBIGUOUS)])
+        .collect()?;
 
-    write_xlsx(&df_pfs, nombre_pfs)?;
-    write_xlsx(&df_bnf, nombre_bnf)?;
+    // Los demás se buscan con nombres parecidos, para revisar a mano los de menor similitud y los
+    // ambiguos
+    let df_parecidos = df_pfs.lazy().filter(exacto.not()).collect()?;
+    let df_parecidos = fuzzy_join(
+        &df_parecidos,
+        &df_bnf,
+        "Nombre",
+        "NOMBRE DEL INVESTIGADOR",
+        &FuzzyJoinOptions {
+            threshold: SIMILITUD_MINIMA,
+            ..Default::default()
+        },
+    )?
+    .drop_many(&["SNombre_right", MATCH_RANK]);
+
+    // El escritor de Excel solo acepta columnas de un solo bloque
+    let df_pfs = df_exactos
+        .vstack(&df_parecidos.select(df_exactos.get_column_names())?)?
+        .agg_chunks();
+
+    write_xlsx(&df_pfs, nombre_pfs, true)?;
+    write_xlsx(&df_bnf, nombre_bnf, true)?;
 
     let df_pfs_unicos = df_pfs
         .lazy()
@@ -95,6 +127,8 @@ fn generar(
             col("NIVEL").alias("Nivel"),
             // Toca cambiar el nombre de la columna en uno de los dos
             col("FECHA FIN DE VIGENCIA").alias("Fin de vigencia"),
+            col(SIMILARITY).alias("Similitud del nombre"),
+            col(AMBIGUOUS).alias("Nombre ambiguo"),
         ])
         .unique_stable(None, UniqueKeepStrategy::First)
         .with_column(
@@ -118,30 +152,7 @@ fn generar(
         .sort("Id Profesor", SortOptions::default())
         .collect()?;
 
-    write_xlsx(&df_pfs_unicos, nombre)?;
-
-    Ok(())
-}
-
-fn name_id() -> impl Fn(Series) -> Result<Option<Series>, PolarsError> {
-    |s| {
-        Ok(Some(Series::from_iter(s.str().unwrap().into_iter().map(
-            |a| String::from_iter(clean_name(a.unwrap_or_default())),
-        ))))
-    }
-}
-
-fn clean_name(name: &str) -> impl Iterator<Item = char> + '_ {
-    chars_to_lower(rm_specials(space_join(
-        name.split(|a| a == ',' || a == ' '),
-    )))
-}
-
-fn write_xlsx(df: &DataFrame, name: &str) -> Result<(), Box<dyn std::error::Error>> {
-    let mut writer = PolarsXlsxWriter::new();
-    writer.set_autofit(true);
-    writer.write_dataframe(df)?;
-    writer.save(name)?;
+    write_xlsx(&df_pfs_unicos, nombre, true)?;
 
     Ok(())
 }
@@ -0,0 +1,450 @@
+//! Validation and normalization of Mexican identifiers: CURP, RFC and CVU
+//!
+//! Identifiers typed by hand often have lowercase letters, spaces or hyphens; they are normalized
+//! before validating them, so `" gode-561231-gr8 "` is read as the RFC `"GODE561231GR8"`.
+//!
+//! ```
+//! use strings::ids::{Curp, Sex};
+//!
+//! let curp = Curp::parse("hegg560427mvzrrl04").unwrap();
+//!
+//! assert_eq!(curp.as_str(), "HEGG560427MVZRRL04");
+//! assert_eq!(curp.sex(), Sex::Female);
+//! assert_eq!(curp.birthdate().to_string(), "1956-04-27");
+//! ```
+
+use std::{fmt, str::FromStr};
+
+use thiserror::Error;
+
+/// Values of the chars used to compute the check digit of a CURP
+const CURP_CHARS: &str = "0123456789ABCDEFGHIJKLMNÑOPQRSTUVWXYZ";
+/// Values of the chars used to compute the check digit of a RFC
+const RFC_CHARS: &str = "0123456789ABCDEFGHIJKLMN&OPQRSTUVWXYZ Ñ";
+
+/// Codes of the states of birth in a CURP, `NE` is used for people born abroad
+const STATES: &[&str] = &[
+    "AS", "BC", "BS", "CC", "CL", "CM", "CS", "CH", "DF", "DG", "GT", "GR", "HG", "JC", "MC", "MN",
+    "MS", "NT", "NL", "OC", "PL", "QT", "QR", "SP", "SL", "SR", "TC", "TS", "TL", "VZ", "YN", "ZS",
+    "NE",
+];
+
+/// Maximum number of digits of a CVU
+const CVU_MAX_DIGITS: usize = 8;
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum IdError {
+    #[error("expected {expected} characters but found {found}")]
+    Length { expected: usize, found: usize },
+    #[error("invalid character `{1}` at position {0}")]
+    Char(usize, char),
+    #[error("invalid date `{0}`")]
+    Date(String),
+    #[error("invalid state code `{0}`")]
+    State(String),
+    #[error("the check digit should be `{expected}` but is `{found}`")]
+    CheckDigit { expected: char, found: char },
+}
+
+/// Sex registered in a CURP
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Sex {
+    Male,
+    Female,
+    /// Written as `X` since 2022
+    NonBinary,
+}
+
+/// A calendar date extracted from an identifier
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Date {
+    pub year: u16,
+    pub month: u8,
+    pub day: u8,
+}
+
+impl Date {
+    /// Days since 1970-01-01, negative for earlier dates
+    #[must_use]
+    pub fn days_since_epoch(self) -> i32 {
+        let (month, day) = (i32::from(self.month), i32::from(self.day));
+        // Years start in March so the leap day is the last one
+        let year = i32::from(self.year) - i32::from(month <= 2);
+        let era = year.div_euclid(400);
+        let year_of_era = year - era * 400;
+        let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+        let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+
+        era * 146_097 + day_of_era - 719_468
+    }
+}
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+/// A valid CURP (Clave Única de Registro de Población) in uppercase
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Curp(String);
+
+impl Curp {
+    /// Normalize and validate `text`, including its check digit
+    pub fn parse(text: &str) -> Result<Self, IdError> {
+        let curp = normalize(text);
+        let chars = curp.chars().collect::<Vec<_>>();
+
+        expect_len(&chars, 18)?;
+        expect_chars(&chars, 0..4, is_letter)?;
+        expect_chars(&chars, 4..10, |c| c.is_ascii_digit())?;
+        expect_chars(&chars, 10..11, |c| matches!(c, 'H' | 'M' | 'X'))?;
+        expect_chars(&chars, 11..16, is_letter)?;
+        expect_chars(&chars, 16..18, |c| c.is_ascii_alphanumeric())?;
+        expect_chars(&chars, 17..18, |c| c.is_ascii_digit())?;
+
+        let state = chars[11..13].iter().collect::<String>();
+        if !STATES.contains(&state.as_str()) {
+            return Err(IdError::State(state));
+        }
+
+        let century = if chars[16].is_ascii_digit() {
+            1900
+        } else {
+            2000
+        };
+        date(&chars[4..10], century)?;
+
+        let sum = weighted_sum(CURP_CHARS, &chars[..17]);
+        let expected = digit((10 - sum % 10) % 10);
+        if chars[17] != expected {
+            return Err(IdError::CheckDigit {
+                expected,
+                found: chars[17],
+            });
+        }
+
+        Ok(Self(curp))
+    }
+
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    #[must_use]
+    pub fn birthdate(&self) -> Date {
+        let chars = self.0.chars().collect::<Vec<_>>();
+        let century = if chars[16].is_ascii_digit() {
+            1900
+        } else {
+            2000
+        };
+
+        date(&chars[4..10], century).unwrap_or(Date {
+            year: century,
+            month: 1,
+            day: 1,
+        })
+    }
+
+    #[must_use]
+    pub fn sex(&self) -> Sex {
+        match self.0.chars().nth(10) {
+            Some('H') => Sex::Male,
+            Some('M') => Sex::Female,
+            _ => Sex::NonBinary,
+        }
+    }
+
+    /// Code of the state of birth, like `"PL"` for Puebla or `"NE"` if born abroad
+    #[must_use]
+    pub fn state(&self) -> &str {
+        let start = self.0.char_indices().nth(11).map_or(0, |(i, _)| i);
+        &self.0[start..start + 2]
+    }
+}
+
+/// Kind of taxpayer of a RFC
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RfcKind {
+    /// Persona física, 13 characters
+    Person,
+    /// Persona moral, 12 characters
+    Company,
+}
+
+/// A valid RFC (Registro Federal de Contribuyentes) in uppercase, with its homoclave
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Rfc(String);
+
+impl Rfc {
+    /// Normalize and validate `text`, including the check digit of its homoclave
+    pub fn parse(text: &str) -> Result<Self, IdError> {
+        let rfc = normalize(text);
+        let chars = rfc.chars().collect::<Vec<_>>();
+
+        let letters = match chars.len() {
+            13 => 4,
+            12 => 3,
+            found => {
+                return Err(IdError::Length {
+                    expected: 13,
+                    found,
+                })
+            }
+        };
+
+        expect_chars(&chars, 0..letters, |c| is_letter(c) || c == '&')?;
+        expect_chars(&chars, letters..letters + 6, |c| c.is_ascii_digit())?;
+        expect_chars(&chars, letters + 6..chars.len(), |c| {
+            c.is_ascii_alphanumeric()
+        })?;
+        // The RFC does not say the century, so `000229` is valid because 2000 was a leap year
+        let digits = &chars[letters..letters + 6];
+        date(digits, 1900).or_else(|_| date(digits, 2000))?;
+
+        // Companies are padded with a space so both kinds have the same weights
+        let padded = [' ']
+            .iter()
+            .take(4 - letters)
+            .chain(&chars[..chars.len() - 1]);
+        let sum = weighted_sum(RFC_CHARS, &padded.copied().collect::<Vec<_>>());
+        let expected = match 11 - sum % 11 {
+            11 => '0',
+            10 => 'A',
+            d => digit(d),
+        };
+
+        let found = chars[chars.len() - 1];
+        if found != expected {
+            return Err(IdError::CheckDigit { expected, found });
+        }
+
+        Ok(Self(rfc))
+    }
+
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    #[must_use]
+    pub fn kind(&self) -> RfcKind {
+        if self.0.chars().count() == 13 {
+            RfcKind::Person
+        } else {
+            RfcKind::Company
+        }
+    }
+}
+
+/// A CVU (Currículum Vitae Único) of CONAHCYT
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Cvu(u32);
+
+impl Cvu {
+    /// Validate `text` as a positive number of up to 8 digits. Leading zeros and a zero decimal
+    /// part, as written by Excel, are ignored
+    pub fn parse(text: &str) -> Result<Self, IdError> {
+        let text = text.trim();
+        let digits = text
+            .strip_suffix(".0")
+            .unwrap_or(text)
+            .trim_start_matches('0');
+
+        if let Some((i, c)) = digits.char_indices().find(|(_, c)| !c.is_ascii_digit()) {
+            return Err(IdError::Char(i, c));
+        }
+        if digits.is_empty() || digits.len() > CVU_MAX_DIGITS {
+            return Err(IdError::Length {
+                expected: CVU_MAX_DIGITS,
+                found: digits.len(),
+            });
+        }
+
+        digits
+            .parse()
+            .map(Self)
+            .map_err(|_| IdError::Char(0, digits.chars().next().unwrap_or('0')))
+    }
+
+    #[must_use]
+    pub fn get(self) -> u32 {
+        self.0
+    }
+}
+
+macro_rules! impl_id {
+    ($id:ty) => {
+        impl FromStr for $id {
+            type Err = IdError;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                Self::parse(s)
+            }
+        }
+
+        impl fmt::Display for $id {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                self.0.fmt(f)
+            }
+        }
+    };
+}
+
+impl_id!(Curp);
+impl_id!(Rfc);
+impl_id!(Cvu);
+
+/// Uppercase `text` without whitespace or hyphens
+fn normalize(text: &str) -> String {
+    text.chars()
+        .filter(|c| !c.is_whitespace() && *c != '-')
+        .flat_map(char::to_uppercase)
+        .collect()
+}
+
+fn expect_len(chars: &[char], expected: usize) -> Result<(), IdError> {
+    if chars.len() == expected {
+        Ok(())
+    } else {
+        Err(IdError::Length {
+            expected,
+            found: chars.len(),
+        })
+    }
+}
+
+fn expect_chars(
+    chars: &[char],
+    range: std::ops::Range<usize>,
+    valid: impl Fn(char) -> bool,
+) -> Result<(), IdError> {
+    match chars[range.clone()].iter().position(|c| !valid(*c)) {
+        Some(i) => Err(IdError::Char(range.start + i, chars[range.start + i])),
+        None => Ok(()),
+    }
+}
+
+/// Date written as `YYMMDD`
+fn date(digits: &[char], century: u16) -> Result<Date, IdError> {
+    let text = digits.iter().collect::<String>();
+    let number = |range: std::ops::Range<usize>| text[range].parse::<u8>().ok();
+
+    let (Some(year), Some(month), Some(day)) = (number(0..2), number(2..4), number(4..6)) else {
+        return Err(IdError::Date(text));
+    };
+    let year = century + u16::from(year);
+    let leap = matches!((year % 4, year % 100, year % 400), (0, 1.., _) | (_, _, 0));
+    let days = match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        2 if leap => 29,
+        2 => 28,
+        _ => return Err(IdError::Date(text)),
+    };
+
+    if (1..=days).contains(&day) {
+        Ok(Date { year, month, day })
+    } else {
+        Err(IdError::Date(text))
+    }
+}
+
+fn is_letter(c: char) -> bool {
+    c.is_ascii_uppercase() || c == 'Ñ'
+}
+
+/// Sum of the values of `chars` in `table` weighted from `chars.len() + 1` down to 2. The chars
+/// must have been validated to be in `table`
+fn weighted_sum(table: &str, chars: &[char]) -> u32 {
+    let len = u32::try_from(chars.len()).unwrap_or(0);
+
+    chars
+        .iter()
+        .zip((2..=len + 1).rev())
+        .map(|(c, weight)| {
+            let value = table.chars().position(|t| t == *c).unwrap_or(0);
+            u32::try_from(value).unwrap_or(0) * weight
+        })
+        .sum()
+}
+
+fn digit(d: u32) -> char {
+    char::from_digit(d, 10).unwrap_or('0')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn curp() {
+        let curp = Curp::parse(" HEGG-560427-MVZRRL04 ").expect("valid CURP");
+
+        assert_eq!(curp.to_string(), "HEGG560427MVZRRL04");
+        assert_eq!(curp.state(), "VZ");
+        assert_eq!(
+            curp.birthdate(),
+            Date {
+                year: 1956,
+                month: 4,
+                day: 27
+            }
+        );
+
+        assert_eq!(
+            Curp::parse("HEGG560427MVZRRL05"),
+            Err(IdError::CheckDigit {
+                expected: '4',
+                found: '5'
+            })
+        );
+        assert!(matches!(
+            Curp::parse("HEGG560431MVZRRL04"),
+            Err(IdError::Date(_))
+        ));
+        assert!(matches!(
+            Curp::parse("HEGG560427MXXRRL04"),
+            Err(IdError::State(_))
+        ));
+        assert!(matches!(
+            Curp::parse("HEGG560427"),
+            Err(IdError::Length { .. })
+        ));
+    }
+
+    #[test]
+    fn days_since_epoch() {
+        let days = |year, month, day| Date { year, month, day }.days_since_epoch();
+
+        assert_eq!(days(1970, 1, 1), 0);
+        assert_eq!(days(1956, 4, 27), -4997);
+        assert_eq!(days(2000, 3, 1), 11_017);
+        assert_eq!(days(2024, 2, 29), 19_782);
+    }
+
+    #[test]
+    fn rfc() {
+        let rfc = "gode561231gr8".parse::<Rfc>().expect("valid RFC");
+
+        assert_eq!(rfc.as_str(), "GODE561231GR8");
+        assert_eq!(rfc.kind(), RfcKind::Person);
+        assert!(Rfc::parse("GODE561231GR9").is_err());
+        assert!(Rfc::parse("GODE561331GR8").is_err());
+
+        // Born on 2000-02-29, the only century where that day exists
+        assert!(Rfc::parse("GODE000229GR4").is_ok());
+        assert!(matches!(Rfc::parse("GODE010229GR4"), Err(IdError::Date(_))));
+    }
+
+    #[test]
+    fn cvu() {
+        assert_eq!(Cvu::parse(" 00123456 ").map(Cvu::get), Ok(123_456));
+        assert_eq!(Cvu::parse("123456.0").map(Cvu::get), Ok(123_456));
+        assert!(Cvu::parse("12A456").is_err());
+        assert!(Cvu::parse("0").is_err());
+        assert!(Cvu::parse("123456789").is_err());
+    }
+}
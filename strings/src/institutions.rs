@@ -0,0 +1,252 @@
+//! Canonical keys for the names of Mexican higher education institutions
+//!
+//! The same institution is written in many ways: `"Universidad Nacional Autónoma de México"`,
+//! `"UNAM"`, `"U.N.A.M."` or `"Universidad Nacional Autonoma de Mexico, A.C."`. The key of a name
+//! is made of its normalized words without stopwords (articles, prepositions, `"universidad"` and
+//! legal suffixes like `"A.C."`), with the known acronyms replaced by the words of the full name,
+//! so all those spellings get the same key.
+//!
+//! ```
+//! use strings::institutions::Institutions;
+//!
+//! let institutions = Institutions::new();
+//!
+//! assert_eq!(
+//!     institutions.key("U.N.A.M."),
+//!     institutions.key("Universidad Nacional Autonoma de Mexico, A. C."),
+//! );
+//! assert_eq!(institutions.acronym("Universidad de las Américas, Puebla"), Some("UDLAP"));
+//! ```
+
+use std::collections::HashMap;
+
+use itertools::Itertools;
+
+use crate::Normalizer;
+
+/// Words ignored in the key, normalized and without periods
+const STOPWORDS: &[&str] = &[
+    "a",
+    "al",
+    "ac",
+    "cv",
+    "de",
+    "del",
+    "e",
+    "el",
+    "en",
+    "la",
+    "las",
+    "lo",
+    "los",
+    "para",
+    "sa",
+    "sapi",
+    "sc",
+    "univ",
+    "universidad",
+    "y",
+];
+
+/// Acronyms of common institutions, as (acronym, full name)
+const ACRONYMS: &[(&str, &str)] = &[
+    ("BUAP", "Benemérita Universidad Autónoma de Puebla"),
+    ("IPN", "Instituto Politécnico Nacional"),
+    ("ITAM", "Instituto Tecnológico Autónomo de México"),
+    (
+        "ITESM",
+        "Instituto Tecnológico y de Estudios Superiores de Monterrey",
+    ),
+    (
+        "ITESO",
+        "Instituto Tecnológico y de Estudios Superiores de Occidente",
+    ),
+    ("UAEMEX", "Universidad Autónoma del Estado de México"),
+    ("UAM", "Universidad Autónoma Metropolitana"),
+    ("UANL", "Universidad Autónoma de Nuevo León"),
+    ("UDG", "Universidad de Guadalajara"),
+    ("UDLAP", "Universidad de las Américas Puebla"),
+    ("UIA", "Universidad Iberoamericana"),
+    ("ULSA", "Universidad La Salle"),
+    ("UNAM", "Universidad Nacional Autónoma de México"),
+    ("UNITEC", "Universidad Tecnológica de México"),
+    ("UP", "Universidad Panamericana"),
+    ("UPAEP", "Universidad Popular Autónoma del Estado de Puebla"),
+    ("UV", "Universidad Veracruzana"),
+    ("UVM", "Universidad del Valle de México"),
+];
+
+/// Returns `true` if the normalized `word` is ignored in the key of an institution
+#[must_use]
+pub fn is_stopword(word: &str) -> bool {
+    STOPWORDS.contains(&word)
+}
+
+#[derive(Debug, Clone)]
+struct Entry {
+    acronym: String,
+    name: String,
+    key: Vec<String>,
+}
+
+/// Dictionary of acronyms used to build the canonical key of institution names
+#[derive(Debug, Clone)]
+pub struct Institutions {
+    /// Acronyms by their normalized form
+    acronyms: HashMap<String, Entry>,
+}
+
+impl Default for Institutions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Institutions {
+    /// Dictionary with the acronyms of common Mexican institutions
+    #[must_use]
+    pub fn new() -> Self {
+        ACRONYMS.iter().fold(Self::empty(), |res, (acronym, name)| {
+            res.with_acronym(*acronym, *name)
+        })
+    }
+
+    /// Dictionary without acronyms
+    #[must_use]
+    pub fn empty() -> Self {
+        Self {
+            acronyms: HashMap::new(),
+        }
+    }
+
+    /// Add or replace the full `name` of `acronym`
+    #[must_use]
+    pub fn with_acronym(mut self, acronym: impl Into<String>, name: impl Into<String>) -> Self {
+        let acronym = acronym.into();
+        let name = name.into();
+        let key = words(&name).filter(|w| !is_stopword(w)).collect();
+
+        self.acronyms
+            .insert(words(&acronym).collect(), Entry { acronym, name, key });
+        self
+    }
+
+    /// Canonical key of the institution `name`
+    #[must_use]
+    pub fn key(&self, name: &str) -> String {
+        words(name)
+            .flat_map(|word| match self.acronyms.get(&word) {
+                Some(acronym) => acronym.key.clone(),
+                None => vec![word],
+            })
+            .filter(|w| !is_stopword(w))
+            // "Universidad Nacional Autónoma de México (UNAM)" repeats every word
+            .unique()
+            .join(" ")
+    }
+
+    /// The full name if `name` is a known acronym, `name` otherwise
+    #[must_use]
+    pub fn expand<'a>(&'a self, name: &'a str) -> &'a str {
+        let mut words = words(name);
+
+        match (words.next(), words.next()) {
+            (Some(word), None) => self
+                .acronyms
+                .get(&word)
+                .map_or(name, |acronym| acronym.name.as_str()),
+            _ => name,
+        }
+    }
+
+    /// The acronym of the institution `name`, if it is known
+    #[must_use]
+    pub fn acronym(&self, name: &str) -> Option<&str> {
+        let key = self.key(name);
+
+        self.acronyms
+            .values()
+            .find(|acronym| acronym.key.join(" ") == key)
+            .map(|acronym| acronym.acronym.as_str())
+    }
+}
+
+/// Normalized words of `name` without periods. Consecutive single letters are joined, so
+/// `"A. C."` and `"U N A M"` become `"ac"` and `"unam"`
+fn words(name: &str) -> impl Iterator<Item = String> {
+    let normalized = Normalizer::new().normalize(name);
+
+    normalized
+        .split(|c: char| !c.is_alphanumeric() && c != '.')
+        .map(|word| word.replace('.', ""))
+        .filter(|word| !word.is_empty())
+        .map(|word| {
+            let letters = word.chars().count() == 1;
+            (word, letters)
+        })
+        .coalesce(|(a, a_letters), (b, b_letters)| {
+            if a_letters && b_letters {
+                Ok((a + &b, true))
+            } else {
+                Err(((a, a_letters), (b, b_letters)))
+            }
+        })
+        .map(|(word, _)| word)
+        .collect_vec()
+        .into_iter()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_key() {
+        let institutions = Institutions::new();
+        let key = institutions.key("Universidad Nacional Autónoma de México");
+
+        assert_eq!(key, "nacional autonoma mexico");
+        assert_eq!(institutions.key("UNAM"), key);
+        assert_eq!(institutions.key("U.N.A.M."), key);
+        assert_eq!(
+            institutions.key("UNIVERSIDAD NACIONAL AUTONOMA DE MEXICO, A.C."),
+            key
+        );
+        assert_eq!(
+            institutions.key("Universidad Nacional Autónoma de México (UNAM)"),
+            key
+        );
+        assert_eq!(
+            institutions.key("ITESM"),
+            institutions.key("Instituto Tecnológico y de Estudios Superiores de Monterrey")
+        );
+    }
+
+    #[test]
+    fn different_key() {
+        let institutions = Institutions::new();
+
+        assert_ne!(
+            institutions.key("Universidad Autónoma de Puebla"),
+            institutions.key("UPAEP")
+        );
+        assert_eq!(
+            institutions.key("ITESM Campus Puebla"),
+            "instituto tecnologico estudios superiores monterrey campus puebla"
+        );
+    }
+
+    #[test]
+    fn acronyms() {
+        let institutions = Institutions::empty().with_acronym("UMAD", "Universidad Madero");
+
+        assert_eq!(institutions.expand("umad"), "Universidad Madero");
+        assert_eq!(institutions.expand("UMAD Puebla"), "UMAD Puebla");
+        assert_eq!(
+            institutions.acronym("Universidad  Madero, A.C."),
+            Some("UMAD")
+        );
+        assert_eq!(institutions.acronym("UNAM"), None);
+        assert_eq!(institutions.key("A. C."), "");
+    }
+}
@@ -5,20 +5,79 @@
 #![allow(clippy::missing_panics_doc)]
 #![deny(clippy::unwrap_used)]
 
-use unicode_normalization::UnicodeNormalization;
+use std::borrow::Cow;
+
+use unicode_normalization::{char::is_combining_mark, UnicodeNormalization};
 
 pub use edit_distance::edit_distance;
+pub use mojibake::{fix_mojibake, is_mojibake};
+pub use norm_key::{Lookup, NormKey, NormMap};
+pub use normalizer::Normalizer;
+pub use phonetic::{phonetic, phonetic_chars, phonetic_key};
+
+pub mod ids;
+pub mod institutions;
+mod mojibake;
+pub mod names;
+mod norm_key;
+mod normalizer;
+pub mod numbers;
+pub mod periodo;
+mod phonetic;
+pub mod safe_names;
+pub mod similarity;
 
 /// Iterate over all contiguous stings of alphabetical characters
-pub fn get_words(cadena: &str) -> impl Iterator<Item = &str> {
+///
+/// Words are separated by any Unicode whitespace (including non-breaking spaces) and the invisible
+/// formatting chars are removed like in [`sanitize_spaces`], see [`is_invisible`]. Only the words
+/// that had invisible chars are allocated
+pub fn get_words(cadena: &str) -> impl Iterator<Item = Cow<'_, str>> {
     get_words_ext(cadena, &[])
 }
 
+/// Like [`get_words`] but only splitting on `' '`, `'\n'`, `'\t'` and `'\r'`, keeping invisible
+/// chars
+pub fn get_words_exact(cadena: &str) -> impl Iterator<Item = &str> {
+    get_words_ext_exact(cadena, &[])
+}
+
+/// Join the words of `contents` with a single space, splitting also on `seps`, folding case and
+/// removing all accents. See [`Normalizer`] to configure the normalization
 pub fn sanitize<'a>(contents: &'a str, seps: &'a [char]) -> impl Iterator<Item = char> + 'a {
-    chars_to_lower(rm_specials(space_join(get_words_ext(contents, seps))))
+    Normalizer::new().sanitize(contents, seps)
 }
 
-pub fn get_words_ext<'a>(cadena: &'a str, extras: &'a [char]) -> impl Iterator<Item = &'a str> {
+/// Like [`get_words`] but splitting also on `extras`
+pub fn get_words_ext<'a>(
+    cadena: &'a str,
+    extras: &'a [char],
+) -> impl Iterator<Item = Cow<'a, str>> {
+    split_words(cadena, extras).map(|word| {
+        if word.contains(is_invisible) {
+            Cow::Owned(strip_invisible(word.chars()).collect())
+        } else {
+            Cow::Borrowed(word)
+        }
+    })
+}
+
+/// Words of [`get_words_ext`] borrowed from `cadena`, they may keep invisible chars inside
+pub(crate) fn split_words<'a>(
+    cadena: &'a str,
+    extras: &'a [char],
+) -> impl Iterator<Item = &'a str> {
+    cadena
+        .split(move |c: char| c.is_whitespace() || extras.contains(&c))
+        .map(|a| a.trim_matches(is_invisible))
+        .filter(|a| !a.is_empty())
+}
+
+/// Like [`get_words_exact`] but splitting also on `extras`
+pub fn get_words_ext_exact<'a>(
+    cadena: &'a str,
+    extras: &'a [char],
+) -> impl Iterator<Item = &'a str> {
     cadena
         .split(' ')
         .flat_map(|a| a.split(&['\n', '\t', '\r']))
@@ -27,6 +86,29 @@ pub fn get_words_ext<'a>(cadena: &'a str, extras: &'a [char]) -> impl Iterator<I
         .filter(|a| !a.is_empty())
 }
 
+/// Returns `true` for the formatting chars that are not displayed, like the soft hyphen
+/// (`U+00AD`), the zero-width space (`U+200B`), the directional marks or the byte order mark
+#[must_use]
+pub fn is_invisible(c: char) -> bool {
+    matches!(
+        c,
+        '\u{AD}'
+            | '\u{180E}'
+            | '\u{200B}'..='\u{200F}'
+            | '\u{202A}'..='\u{202E}'
+            | '\u{2060}'..='\u{2064}'
+            | '\u{2066}'..='\u{2069}'
+            | '\u{FEFF}'
+    )
+}
+
+/// Remove the invisible formatting chars, see [`is_invisible`]
+pub fn strip_invisible<'a>(
+    chars: impl Iterator<Item = char> + 'a,
+) -> impl Iterator<Item = char> + 'a {
+    chars.filter(|c| !is_invisible(*c))
+}
+
 /// Iterator that yields a space between each item from the original iterator
 pub fn space_join<'a>(mut iter: impl Iterator<Item = &'a str>) -> impl Iterator<Item = &'a str> {
     SpaceJoiner {
@@ -44,11 +126,12 @@ pub fn replace_chars<'a>(
     chars.map(move |c| if matches.contains(&c) { to } else { c })
 }
 
-/// Compare the first letter of each word
+/// Compare the first letter of each word. See [`names::FullName::compatibility`] to compare
+/// personal names that may be reordered or miss some words
 #[must_use]
 pub fn compare_words(slice: &str, with: &str) -> bool {
-    let words1 = get_words(slice);
-    let words2 = get_words(with);
+    let words1 = split_words(slice, &[]);
+    let words2 = split_words(with, &[]);
 
     let first_chars1 = n_chars(words1, 1);
     let first_chars2 = n_chars(words2, 1);
@@ -56,31 +139,30 @@ pub fn compare_words(slice: &str, with: &str) -> bool {
     first_chars1.eq(first_chars2)
 }
 
+/// Remove all combining marks from the words, `"ñ"` becomes `"n"`
 pub fn rm_specials<'a>(
     word: impl Iterator<Item = &'a str> + 'a,
 ) -> impl Iterator<Item = char> + 'a {
-    word.flat_map(move |w| {
-        w.chars()
-            .map(move |c| c.nfd().next().expect("All chars have at least one char?"))
-    })
+    rm_specials_char(word.flat_map(str::chars))
 }
 
+/// Remove all combining marks from the chars, `'ñ'` becomes `'n'`
 pub fn rm_specials_char<'a>(
     word: impl Iterator<Item = char> + 'a,
 ) -> impl Iterator<Item = char> + 'a {
-    word.map(move |c| c.nfd().next().expect("All chars have at least one char?"))
+    word.flat_map(|c| c.nfd().filter(|d| !is_combining_mark(*d)))
 }
 
 pub fn chars_to_lower<'a>(
     chars: impl Iterator<Item = char> + 'a,
 ) -> impl Iterator<Item = char> + 'a {
-    chars.map(|c| c.to_ascii_lowercase())
+    chars.flat_map(char::to_lowercase)
 }
 
 pub fn chars_to_upper<'a>(
     chars: impl Iterator<Item = char> + 'a,
 ) -> impl Iterator<Item = char> + 'a {
-    chars.map(|c| c.to_ascii_uppercase())
+    chars.flat_map(char::to_uppercase)
 }
 
 pub fn n_chars<'a>(
@@ -90,7 +172,8 @@ pub fn n_chars<'a>(
     word.flat_map(move |w| w.chars().take(n).filter_map(|c| c.to_lowercase().next()))
 }
 
-/// Iterator that yields each word in a string with a space between each one
+/// Iterator that yields each word in a string with a space between each one. The words borrow
+/// from `string` so they may keep invisible chars inside, [`sanitize_spaces_chars`] removes them
 ///
 /// # Examples
 ///
@@ -102,7 +185,7 @@ pub fn n_chars<'a>(
 /// assert_eq!(sanitize_spaces_iter(text).collect::<Vec<_>>(), vec!["Hello,", " ", "world!"]);
 /// ```
 pub fn sanitize_spaces_iter(string: &str) -> impl Iterator<Item = &str> {
-    space_join(get_words(string))
+    space_join(split_words(string, &[]))
 }
 
 /// Iterator that yields each word in a string with a space between each one
@@ -115,13 +198,23 @@ pub fn sanitize_spaces_iter(string: &str) -> impl Iterator<Item = &str> {
 /// let text = "\t\t\n Hello,\n\n\t \r\n world!\n\t\n";
 ///
 /// assert_eq!(sanitize_spaces(text), "Hello, world!".to_string());
+/// assert_eq!(sanitize_spaces("Id\u{A0}Pro\u{AD}fesor\u{200B}"), "Id Profesor".to_string());
 /// ```
 #[must_use]
 pub fn sanitize_spaces(string: &str) -> String {
-    let words = get_words(string);
-    let joinner = space_join(words);
+    sanitize_spaces_chars(string).collect()
+}
+
+/// Chars of [`sanitize_spaces`]
+pub fn sanitize_spaces_chars(string: &str) -> impl Iterator<Item = char> + '_ {
+    strip_invisible(sanitize_spaces_iter(string).flat_map(str::chars))
+}
 
-    joinner.collect::<String>()
+/// Like [`sanitize_spaces`] but only splitting on `' '`, `'\n'`, `'\t'` and `'\r'`, keeping invisible
+/// chars
+#[must_use]
+pub fn sanitize_spaces_exact(string: &str) -> String {
+    space_join(get_words_exact(string)).collect()
 }
 
 impl<'a, T: Iterator<Item = &'a str>> Iterator for SpaceJoiner<'a, T> {
@@ -160,7 +253,7 @@ mod tests {
         let inp = "\n\n\t\n   hola\t\t\n \n\t\n como\n\n\t\t\n\n\t \n \testas\n\n\t";
 
         let expect = expect.into_iter().collect::<Vec<_>>().join(" ");
-        let got = space_join(get_words(inp)).collect::<String>();
+        let got = sanitize_spaces_iter(inp).collect::<String>();
 
         assert_eq!(expect, got);
     }
@@ -178,12 +271,47 @@ mod tests {
         );
     }
 
+    #[test]
+    fn unicode_spaces() {
+        let inp = "\u{FEFF}Id\u{A0}Profesor\u{202F}\u{200B}Nombre\u{3000}Pro\u{AD}fesor";
+
+        assert_eq!(
+            get_words(inp).collect::<Vec<_>>(),
+            ["Id", "Profesor", "Nombre", "Profesor"]
+        );
+        assert_eq!(sanitize_spaces(inp), "Id Profesor Nombre Profesor");
+        assert_eq!(
+            sanitize(inp, &[]).collect::<String>(),
+            "id profesor nombre profesor"
+        );
+        assert_eq!(get_words_exact("Id\u{A0}Profesor").count(), 1);
+        assert_eq!(sanitize_spaces_exact(" Id\u{200B}  X "), "Id\u{200B} X");
+    }
+
+    #[test]
+    fn invisible_inside_words() {
+        let inp = "Id\u{AD}Profesor  Nombre\u{200B}Completo";
+
+        assert_eq!(
+            get_words(inp).collect::<Vec<_>>(),
+            ["IdProfesor", "NombreCompleto"]
+        );
+        assert_eq!(
+            sanitize_spaces(inp),
+            get_words(inp).collect::<Vec<_>>().join(" ")
+        );
+        assert!(matches!(
+            get_words("Id Profesor").next(),
+            Some(Cow::Borrowed("Id"))
+        ));
+    }
+
     #[test]
     fn take_chars_2() {
         let str = "Hola me llamo daniel";
         let expected = &['h', 'o', 'm', 'e', 'l', 'l', 'd', 'a'];
 
-        let res = n_chars(get_words(str), 2).collect::<Vec<_>>();
+        let res = n_chars(split_words(str, &[]), 2).collect::<Vec<_>>();
 
         assert_eq!(res.as_slice(), expected.as_slice());
     }
@@ -193,7 +321,7 @@ mod tests {
         let str = "Hola me llamo Daniel";
         let expected = &['h', 'o', ' ', 'm', 'e', ' ', 'l', 'l', ' ', 'd', 'a'];
 
-        let res = n_chars(space_join(get_words(str)), 2).collect::<Vec<_>>();
+        let res = n_chars(sanitize_spaces_iter(str), 2).collect::<Vec<_>>();
 
         assert_eq!(res.as_slice(), expected.as_slice());
     }
@@ -207,10 +335,19 @@ mod tests {
             'j', 'a',
         ];
 
-        let res = rm_specials(space_join(get_words(str))).collect::<Vec<_>>();
+        let res = rm_specials(sanitize_spaces_iter(str)).collect::<Vec<_>>();
         assert_eq!(res.as_slice(), expected.as_slice());
     }
 
+    #[test]
+    fn lower_non_ascii() {
+        let res = chars_to_lower("ÑÉÁ".chars()).collect::<String>();
+        assert_eq!(res, "ñéá");
+
+        let res = sanitize("  PEÑA,  ÁVILA ", &[',']).collect::<String>();
+        assert_eq!(res, "pena avila");
+    }
+
     #[test]
     fn compare() {
         let name1 = "Juan P. Rodriguez Pérez";
@@ -0,0 +1,151 @@
+use std::borrow::Cow;
+
+/// Times the repair is applied, text that went through a bad conversion twice is common in files
+/// that were opened and saved again
+const MAX_PASSES: usize = 3;
+
+/// Chars of Windows-1252 for the bytes `0x80..=0x9F`, `None` for the bytes it leaves undefined
+const CP1252_HIGH: [Option<char>; 32] = [
+    Some('€'),
+    None,
+    Some('‚'),
+    Some('ƒ'),
+    Some('„'),
+    Some('…'),
+    Some('†'),
+    Some('‡'),
+    Some('ˆ'),
+    Some('‰'),
+    Some('Š'),
+    Some('‹'),
+    Some('Œ'),
+    None,
+    Some('Ž'),
+    None,
+    None,
+    Some('‘'),
+    Some('’'),
+    Some('“'),
+    Some('”'),
+    Some('•'),
+    Some('–'),
+    Some('—'),
+    Some('˜'),
+    Some('™'),
+    Some('š'),
+    Some('›'),
+    Some('œ'),
+    None,
+    Some('ž'),
+    Some('Ÿ'),
+];
+
+/// Repair text that was encoded as UTF-8 but decoded as Latin-1 or Windows-1252, like
+/// `"InstituciÃ³n"` for `"Institución"`
+///
+/// Each run of non-ASCII chars is converted back to the bytes it was decoded from and is only
+/// replaced if those bytes are valid UTF-8, so correctly written text like `"Peña"` is kept, even
+/// in a string where other words are broken. Text broken more than once is repaired too.
+///
+/// # Examples
+///
+/// ```
+/// use strings::fix_mojibake;
+///
+/// assert_eq!(fix_mojibake("InstituciÃ³n"), "Institución");
+/// assert_eq!(fix_mojibake("PeÃ±a Peña"), "Peña Peña");
+/// assert_eq!(fix_mojibake("InstituciÃƒÂ³n"), "Institución");
+/// assert_eq!(fix_mojibake("Año"), "Año");
+/// ```
+#[must_use]
+pub fn fix_mojibake(text: &str) -> Cow<'_, str> {
+    let mut res = Cow::Borrowed(text);
+
+    for _ in 0..MAX_PASSES {
+        match repair_pass(&res) {
+            Some(fixed) => res = Cow::Owned(fixed),
+            None => break,
+        }
+    }
+
+    res
+}
+
+/// Returns `true` if `text` has chars that look like UTF-8 decoded as Latin-1 or Windows-1252, see
+/// [`fix_mojibake`]
+#[must_use]
+pub fn is_mojibake(text: &str) -> bool {
+    repair_pass(text).is_some()
+}
+
+/// Repair each run of non-ASCII chars once, `None` if nothing changed
+fn repair_pass(text: &str) -> Option<String> {
+    let mut res = String::with_capacity(text.len());
+    let mut changed = false;
+    let mut rest = text;
+
+    while let Some(start) = rest.find(|c: char| !c.is_ascii()) {
+        res.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let end = rest.find(|c: char| c.is_ascii()).unwrap_or(rest.len());
+        let run = &rest[..end];
+        rest = &rest[end..];
+
+        match decode_run(run) {
+            Some(fixed) => {
+                res.push_str(&fixed);
+                changed = true;
+            }
+            None => res.push_str(run),
+        }
+    }
+
+    res.push_str(rest);
+    changed.then_some(res)
+}
+
+/// Decode the bytes of `run` as UTF-8, `None` if a char is not from Latin-1 or Windows-1252 or if
+/// the bytes are not valid UTF-8
+fn decode_run(run: &str) -> Option<String> {
+    let bytes = run.chars().map(to_byte).collect::<Option<Vec<_>>>()?;
+    String::from_utf8(bytes).ok()
+}
+
+/// Byte that decodes to `c` in Windows-1252, falling back to Latin-1 for the bytes that
+/// Windows-1252 leaves undefined
+fn to_byte(c: char) -> Option<u8> {
+    if let Ok(byte) = u8::try_from(c) {
+        let undefined =
+            (0x80..=0x9F).contains(&byte) && CP1252_HIGH[usize::from(byte - 0x80)].is_none();
+        return (byte >= 0xA0 || undefined).then_some(byte);
+    }
+
+    CP1252_HIGH
+        .iter()
+        .position(|high| *high == Some(c))
+        .and_then(|i| u8::try_from(i + 0x80).ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn repairs_common_spanish() {
+        assert_eq!(fix_mojibake("MÃ©xico"), "México");
+        assert_eq!(fix_mojibake("NÃºÃ±ez"), "Núñez");
+        assert_eq!(fix_mojibake("Ã\u{81}vila"), "Ávila");
+        assert_eq!(fix_mojibake("â€œSNIIâ€\u{9D}"), "“SNII”");
+        assert!(is_mojibake("INVESTIGACIÃ“N"));
+        assert_eq!(fix_mojibake("INVESTIGACIÃ“N"), "INVESTIGACIÓN");
+    }
+
+    #[test]
+    fn keeps_valid_text() {
+        for text in ["Ñandú", "ÁVILA MUÑOZ", "–", "10 €", "日本", ""] {
+            assert!(!is_mojibake(text), "{text}");
+            assert!(matches!(fix_mojibake(text), Cow::Borrowed(_)), "{text}");
+        }
+    }
+}
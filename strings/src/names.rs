@@ -0,0 +1,636 @@
+//! Parsing of Mexican/Spanish personal names into given names and surnames
+//!
+//! Names may come as `"Apellidos, Nombres"` or as `"Nombres Apellido Paterno Apellido Materno"`.
+//! Surname particles (`"de la"`, `"del"`, `"de los"`, ...) are kept together with the word they
+//! precede, single letters are treated as initials and common abbreviations like `"Ma."` or
+//! `"Fco."` are expanded.
+
+use std::fmt;
+
+use crate::{get_words_ext, similarity::jaro_winkler, split_words, Normalizer};
+
+/// Words that join with the following word to form a single name or surname
+const PARTICLES: &[&str] = &[
+    "de", "del", "la", "las", "los", "da", "das", "do", "dos", "san", "santa", "van", "von",
+];
+
+/// Abbreviations of given names, as (normalized abbreviation without period, full name)
+const ABBREVIATIONS: &[(&str, &str)] = &[
+    ("ma", "María"),
+    ("fco", "Francisco"),
+    ("gpe", "Guadalupe"),
+    ("ml", "Manuel"),
+];
+
+/// Given names that are rarely used as surnames, normalized. Used to tell apart compound given
+/// names like `"María José Pérez"` from `"Juan Pérez García"` when there is no comma
+const GIVEN_NAMES: &[&str] = &[
+    "alberto",
+    "alejandra",
+    "alejandro",
+    "alicia",
+    "ana",
+    "andrea",
+    "andres",
+    "angel",
+    "angelica",
+    "antonio",
+    "beatriz",
+    "camila",
+    "carlos",
+    "carmen",
+    "carolina",
+    "cecilia",
+    "concepcion",
+    "consuelo",
+    "cristina",
+    "daniel",
+    "daniela",
+    "dolores",
+    "eduardo",
+    "elena",
+    "elizabeth",
+    "enrique",
+    "esther",
+    "eugenia",
+    "fernanda",
+    "fernando",
+    "francisco",
+    "gabriel",
+    "gabriela",
+    "guadalupe",
+    "ignacio",
+    "ines",
+    "isabel",
+    "javier",
+    "jesus",
+    "jorge",
+    "jose",
+    "josefina",
+    "juan",
+    "juana",
+    "laura",
+    "lourdes",
+    "lucia",
+    "luis",
+    "luisa",
+    "luz",
+    "manuel",
+    "margarita",
+    "maria",
+    "mariana",
+    "mario",
+    "mercedes",
+    "miguel",
+    "monica",
+    "pablo",
+    "patricia",
+    "pilar",
+    "rafael",
+    "refugio",
+    "ricardo",
+    "roberto",
+    "rocio",
+    "rosa",
+    "rosario",
+    "socorro",
+    "sofia",
+    "teresa",
+    "valentina",
+    "veronica",
+    "victoria",
+];
+
+/// Minimum [`jaro_winkler`] similarity for two full words to be paired
+const SIMILAR_THRESHOLD: f64 = 0.85;
+/// Score of an initial paired with a full word that starts with it
+const INITIAL_SCORE: f64 = 0.8;
+/// Score of two equal initials
+const BOTH_INITIALS_SCORE: f64 = 0.7;
+/// Factor applied to pairs of a given name with a surname
+const REORDER_FACTOR: f64 = 0.9;
+/// Fraction of its weight that a missing word costs, compared to a conflicting one
+const MISSING_COST: f64 = 0.25;
+
+/// A personal name split into given names and surnames
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FullName {
+    /// Given names in order, compound names like `"María José"` are two items
+    pub given: Vec<String>,
+    /// First surname, including its particles (e.g. `"de la Garza"`)
+    pub paternal: Option<String>,
+    /// Second surname, including its particles
+    pub maternal: Option<String>,
+}
+
+impl FullName {
+    /// Parse a full name written either as `"Apellidos, Nombres"` or as `"Nombres Apellidos"`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use strings::names::FullName;
+    ///
+    /// let name = FullName::parse("de la Garza Pérez, María José");
+    ///
+    /// assert_eq!(name.given, ["María", "José"]);
+    /// assert_eq!(name.paternal.as_deref(), Some("de la Garza"));
+    /// assert_eq!(name.maternal.as_deref(), Some("Pérez"));
+    /// assert_eq!(name.key(), "maria jose garza perez");
+    /// ```
+    #[must_use]
+    pub fn parse(text: &str) -> Self {
+        if let Some((surnames, given)) = text.split_once(',') {
+            let given = units(given);
+
+            if !given.is_empty() {
+                let mut surnames = units(surnames).into_iter();
+                let paternal = surnames.next();
+                let maternal = join(surnames);
+
+                return Self {
+                    given,
+                    paternal,
+                    maternal,
+                };
+            }
+        }
+
+        let mut units = units(text);
+        let n_given = match units.len() {
+            0..=2 => 1,
+            n if n > 3 => n - 2,
+            _ if is_given_name(&units[1]) => 2,
+            _ => 1,
+        }
+        .min(units.len());
+
+        let mut surnames = units.split_off(n_given).into_iter();
+        let paternal = surnames.next();
+        let maternal = join(surnames);
+
+        Self {
+            given: units,
+            paternal,
+            maternal,
+        }
+    }
+
+    /// Build a name from separate columns of given names, paternal and maternal surnames
+    #[must_use]
+    pub fn from_parts(given: &str, paternal: &str, maternal: &str) -> Self {
+        Self {
+            given: units(given),
+            paternal: join(units(paternal).into_iter()),
+            maternal: join(units(maternal).into_iter()),
+        }
+    }
+
+    /// Iterate over the surnames that are present
+    pub fn surnames(&self) -> impl Iterator<Item = &str> {
+        self.paternal
+            .iter()
+            .chain(self.maternal.iter())
+            .map(String::as_str)
+    }
+
+    /// Canonical key with all given names and surnames, folded, without accents, particles or
+    /// periods, joined by a single space
+    #[must_use]
+    pub fn key(&self) -> String {
+        let parts = self.given.iter().map(String::as_str).chain(self.surnames());
+
+        join_keys(
+            parts
+                .flat_map(key_words)
+                .map(|w| Normalizer::new().normalize(w)),
+        )
+    }
+
+    /// Key made with the first `n` chars of the first given name and of each surname word, i.e.
+    /// `"Rodríguez Pérez, Juan Pablo"` becomes `"ju ro pe"` with `n = 2`
+    #[must_use]
+    pub fn initials_key(&self, n: usize) -> String {
+        let first_given = self
+            .given
+            .first()
+            .into_iter()
+            .flat_map(|g| key_words(g).take(1));
+        let surnames = self.surnames().flat_map(key_words);
+
+        join_keys(
+            first_given
+                .chain(surnames)
+                .map(|w| Normalizer::new().chars(w.chars()).take(n).collect()),
+        )
+    }
+
+    /// How likely it is that `self` and `other` are the same person
+    ///
+    /// Words are paired regardless of their order, an initial pairs with a full word that starts
+    /// with it and full words pair if they are similar enough. A second given name or a maternal
+    /// surname that is present in only one of the names barely lowers the score, while words that
+    /// could not be paired with the words left on the other side lower it a lot.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use strings::names::{FullName, PairKind};
+    ///
+    /// let a = FullName::parse("Juan P. Rodríguez Pérez");
+    /// let b = FullName::parse("Rodriguez, Juan Pablo");
+    /// let compatibility = a.compatibility(&b);
+    ///
+    /// assert!(compatibility.score > 0.85);
+    /// assert_eq!(compatibility.alignment[1].kind, PairKind::Initial);
+    /// assert_eq!(compatibility.alignment[3].kind, PairKind::Missing);
+    /// ```
+    #[must_use]
+    pub fn compatibility(&self, other: &FullName) -> Compatibility {
+        let left = self.words();
+        let right = other.words();
+
+        let mut candidates = Vec::new();
+        for (i, a) in left.iter().enumerate() {
+            for (j, b) in right.iter().enumerate() {
+                if let Some((kind, score)) = a.compare(b) {
+                    candidates.push((i, j, kind, score));
+                }
+            }
+        }
+        // Best pairs first, ties are broken by word order so the alignment is deterministic
+        candidates.sort_by(|a, b| b.3.total_cmp(&a.3).then((a.0, a.1).cmp(&(b.0, b.1))));
+
+        let mut left_pairs = vec![None; left.len()];
+        let mut right_paired = vec![false; right.len()];
+        for (i, j, kind, score) in candidates {
+            if left_pairs[i].is_none() && !right_paired[j] {
+                left_pairs[i] = Some((j, kind, score));
+                right_paired[j] = true;
+            }
+        }
+
+        let left_open =
+            |role| (left.iter().zip(&left_pairs)).any(|(w, pair)| pair.is_none() && w.role == role);
+        let right_open =
+            |role| (right.iter().zip(&right_paired)).any(|(w, paired)| !paired && w.role == role);
+        let unpaired = |word: &Word<'_>, conflict: bool| {
+            let (kind, cost) = if conflict {
+                (PairKind::Conflict, 1.)
+            } else {
+                (PairKind::Missing, MISSING_COST)
+            };
+            (kind, word.weight * cost)
+        };
+
+        let mut alignment = Vec::with_capacity(left.len().max(right.len()));
+        let mut matched = 0.;
+        let mut total = 0.;
+
+        for (word, pair) in left.iter().zip(&left_pairs) {
+            if let Some((j, kind, score)) = *pair {
+                let weight = word.weight.max(right[j].weight);
+                matched += score * weight;
+                total += weight;
+                alignment.push(WordPair::new(Some(word), Some(&right[j]), kind, score));
+            } else {
+                let (kind, cost) = unpaired(word, right_open(word.role));
+                total += cost;
+                alignment.push(WordPair::new(Some(word), None, kind, 0.));
+            }
+        }
+
+        for (word, _) in right
+            .iter()
+            .zip(&right_paired)
+            .filter(|(_, paired)| !**paired)
+        {
+            let (kind, cost) = unpaired(word, left_open(word.role));
+            total += cost;
+            alignment.push(WordPair::new(None, Some(word), kind, 0.));
+        }
+
+        let score = if matched > 0. { matched / total } else { 0. };
+
+        Compatibility { score, alignment }
+    }
+
+    /// Words relevant for [`FullName::compatibility`] with their role and weight
+    fn words(&self) -> Vec<Word<'_>> {
+        let given = (self.given.iter().flat_map(|g| key_words(g)).enumerate())
+            .map(|(i, w)| Word::new(w, Role::Given, if i == 0 { 1. } else { 0.5 }));
+        let paternal = (self.paternal.iter().flat_map(|p| key_words(p)))
+            .map(|w| Word::new(w, Role::Surname, 1.));
+        let maternal = (self.maternal.iter().flat_map(|m| key_words(m)))
+            .map(|w| Word::new(w, Role::Surname, 0.5));
+
+        given.chain(paternal).chain(maternal).collect()
+    }
+}
+
+/// Result of [`FullName::compatibility`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Compatibility {
+    /// Confidence (`0..=1`) that both names belong to the same person
+    pub score: f64,
+    /// The words of both names and how they were paired, in the order of the first name followed
+    /// by the unpaired words of the second one
+    pub alignment: Vec<WordPair>,
+}
+
+/// A word of each name paired by [`FullName::compatibility`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct WordPair {
+    pub left: Option<String>,
+    pub right: Option<String>,
+    pub kind: PairKind,
+    /// Similarity (`0..=1`) of both words
+    pub score: f64,
+}
+
+impl WordPair {
+    fn new(left: Option<&Word<'_>>, right: Option<&Word<'_>>, kind: PairKind, score: f64) -> Self {
+        Self {
+            left: left.map(|w| w.text.to_string()),
+            right: right.map(|w| w.text.to_string()),
+            kind,
+            score,
+        }
+    }
+}
+
+/// How two words were paired
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PairKind {
+    /// Both words are equal ignoring case and accents
+    Exact,
+    /// Both words are similar, like `"Gonzales"` and `"González"`
+    Similar,
+    /// At least one of the words is an initial of the other one
+    Initial,
+    /// The word has no counterpart in the other name, like a missing maternal surname
+    Missing,
+    /// The word could not be paired with the words of the same kind left in the other name
+    Conflict,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Role {
+    Given,
+    Surname,
+}
+
+struct Word<'a> {
+    text: &'a str,
+    normalized: String,
+    role: Role,
+    weight: f64,
+}
+
+impl<'a> Word<'a> {
+    fn new(text: &'a str, role: Role, weight: f64) -> Self {
+        Self {
+            text,
+            normalized: Normalizer::new().normalize(text),
+            role,
+            weight,
+        }
+    }
+
+    fn compare(&self, other: &Word<'_>) -> Option<(PairKind, f64)> {
+        let (a, b) = (self.normalized.as_str(), other.normalized.as_str());
+        let first = |w: &str| w.chars().next();
+
+        let (kind, score) = match (is_initial(a), is_initial(b)) {
+            _ if a == b && !is_initial(a) => (PairKind::Exact, 1.),
+            (true, true) if a == b => (PairKind::Initial, BOTH_INITIALS_SCORE),
+            (true, false) | (false, true) if first(a) == first(b) => {
+                (PairKind::Initial, INITIAL_SCORE)
+            }
+            (false, false) => {
+                let score = jaro_winkler(a, b);
+                if score < SIMILAR_THRESHOLD {
+                    return None;
+                }
+                (PairKind::Similar, score)
+            }
+            _ => return None,
+        };
+
+        let factor = if self.role == other.role {
+            1.
+        } else {
+            REORDER_FACTOR
+        };
+
+        Some((kind, score * factor))
+    }
+}
+
+impl fmt::Display for FullName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let parts = self.given.iter().map(String::as_str).chain(self.surnames());
+
+        for (i, part) in parts.enumerate() {
+            if i > 0 {
+                f.write_str(" ")?;
+            }
+            f.write_str(part)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns true if `word` is a surname particle like `"de"`, `"la"` or `"del"`
+#[must_use]
+pub fn is_particle(word: &str) -> bool {
+    let word = Normalizer::new().normalize(word);
+    PARTICLES.contains(&word.as_str())
+}
+
+/// Returns true if `part` is a single letter, optionally followed by a period
+#[must_use]
+pub fn is_initial(part: &str) -> bool {
+    let mut chars = part.trim_end_matches('.').chars();
+    chars.next().is_some_and(char::is_alphabetic) && chars.next().is_none()
+}
+
+/// Returns true if `part` is a given name that is rarely used as a surname, ignoring particles
+#[must_use]
+pub fn is_given_name(part: &str) -> bool {
+    let mut words = key_words(part).peekable();
+    words.peek().is_some()
+        && words.all(|w| GIVEN_NAMES.contains(&Normalizer::new().normalize(w).as_str()))
+}
+
+/// Words of a name part that are relevant for keys, i.e. not particles. The parts come from
+/// [`units`] so they have no invisible chars
+fn key_words(part: &str) -> impl Iterator<Item = &str> {
+    split_words(part, &['.']).filter(|w| !is_particle(w))
+}
+
+fn join_keys(words: impl Iterator<Item = String>) -> String {
+    let mut res = String::new();
+
+    for word in words.filter(|w| !w.is_empty()) {
+        if !res.is_empty() {
+            res.push(' ');
+        }
+        res.push_str(&word);
+    }
+
+    res
+}
+
+fn join(parts: impl Iterator<Item = String>) -> Option<String> {
+    let joined = join_keys(parts);
+    (!joined.is_empty()).then_some(joined)
+}
+
+/// Canonical form of a single word: expands abbreviations and writes initials as `"P."`
+fn canonical_word(word: &str) -> String {
+    let normalized = Normalizer::new().normalize(word.trim_end_matches('.'));
+
+    if let Some((_, full)) = ABBREVIATIONS.iter().find(|(abbr, _)| *abbr == normalized) {
+        (*full).to_string()
+    } else if is_initial(word) {
+        word.chars()
+            .take(1)
+            .flat_map(char::to_uppercase)
+            .chain(['.'])
+            .collect()
+    } else {
+        word.to_string()
+    }
+}
+
+/// Group the words of `text` into name units, attaching particles to the word that follows them
+fn units(text: &str) -> Vec<String> {
+    let mut res = Vec::new();
+    let mut current = String::new();
+
+    // Periods separate initials written together like "J.P."
+    let words = get_words_ext(text, &[',']).collect::<Vec<_>>();
+    for word in words.iter().flat_map(|w| w.split_inclusive('.')) {
+        let word = word.trim();
+        if word.is_empty() || word == "." {
+            continue;
+        }
+
+        if !current.is_empty() {
+            current.push(' ');
+        }
+        current.push_str(&canonical_word(word));
+
+        if !is_particle(word) {
+            res.push(std::mem::take(&mut current));
+        }
+    }
+
+    // Trailing particles belong to the last unit
+    if !current.is_empty() {
+        match res.last_mut() {
+            Some(last) => {
+                last.push(' ');
+                last.push_str(&current);
+            }
+            None => res.push(current),
+        }
+    }
+
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn comma_order() {
+        let name = FullName::parse("Rodríguez Pérez, Juan Pablo");
+
+        assert_eq!(name.given, ["Juan", "Pablo"]);
+        assert_eq!(name.paternal.as_deref(), Some("Rodríguez"));
+        assert_eq!(name.maternal.as_deref(), Some("Pérez"));
+        assert_eq!(name.initials_key(2), "ju ro pe");
+    }
+
+    #[test]
+    fn given_first_order() {
+        let name = FullName::parse("Juan Carlos de los Santos López");
+
+        assert_eq!(name.given, ["Juan", "Carlos"]);
+        assert_eq!(name.paternal.as_deref(), Some("de los Santos"));
+        assert_eq!(name.maternal.as_deref(), Some("López"));
+        assert_eq!(name.to_string(), "Juan Carlos de los Santos López");
+    }
+
+    #[test]
+    fn compound_given_names() {
+        let name = FullName::parse("María José Hernández");
+        assert_eq!(name.given, ["María", "José"]);
+        assert_eq!(name.paternal.as_deref(), Some("Hernández"));
+        assert_eq!(name.maternal, None);
+
+        let name = FullName::parse("Ma. del Carmen Ruiz");
+        assert_eq!(name.given, ["María", "del Carmen"]);
+        assert_eq!(name.key(), "maria carmen ruiz");
+
+        let name = FullName::parse("Pedro Ruiz Hernández");
+        assert_eq!(name.given, ["Pedro"]);
+        assert_eq!(name.maternal.as_deref(), Some("Hernández"));
+    }
+
+    #[test]
+    fn initials() {
+        let name = FullName::parse("J.P. rodríguez p.");
+
+        assert_eq!(name.given, ["J.", "P."]);
+        assert_eq!(name.paternal.as_deref(), Some("rodríguez"));
+        assert_eq!(name.maternal.as_deref(), Some("P."));
+        assert!(is_initial("p."));
+        assert!(!is_initial("Pe"));
+    }
+
+    #[test]
+    fn compatibility() {
+        let score = |a, b| FullName::parse(a).compatibility(&FullName::parse(b)).score;
+        let full = "Juan P. Rodríguez Pérez";
+
+        assert!(score(full, "Juan Pablo Rodriguez Perez") > 0.95);
+        assert!(score(full, "Juan Rodríguez") > 0.85);
+        assert!(score(full, "Rodríguez Pérez Juan") > 0.85);
+        assert!(score(full, "Juan Pablo Gonzales Perez") < 0.75);
+        assert!(score(full, "J P R P") < score(full, "Juan Pablo Rodríguez Pérez"));
+        assert!(score("Juan Carlos Rodríguez", "Juan Pablo Rodríguez") < 0.85);
+        assert!(score("Gonzales López, Ana", "González López, Ana") > 0.95);
+        assert!(score("Ana López", "Pedro Ruiz") < f64::EPSILON);
+
+        let alignment = FullName::parse("Juan Pablo Rodríguez Pérez")
+            .compatibility(&FullName::parse("Juan Carlos Rodríguez"))
+            .alignment;
+        let kinds = alignment.iter().map(|p| p.kind).collect::<Vec<_>>();
+        assert_eq!(
+            kinds,
+            [
+                PairKind::Exact,
+                PairKind::Conflict,
+                PairKind::Exact,
+                PairKind::Missing,
+                PairKind::Conflict
+            ]
+        );
+        assert_eq!(alignment[4].right.as_deref(), Some("Carlos"));
+    }
+
+    #[test]
+    fn from_parts() {
+        let name = FullName::from_parts("  Ana  Sofía", "DE LA GARZA", "");
+
+        assert_eq!(name.given, ["Ana", "Sofía"]);
+        assert_eq!(name.paternal.as_deref(), Some("DE LA GARZA"));
+        assert_eq!(name.maternal, None);
+        assert_eq!(name.initials_key(2), "an ga");
+    }
+}
@@ -0,0 +1,333 @@
+use std::{
+    borrow::Borrow,
+    collections::HashMap,
+    fmt,
+    hash::{Hash, Hasher},
+};
+
+use crate::{similarity::jaro_winkler, Normalizer};
+
+/// Minimum [`jaro_winkler`] similarity between normalized keys to report a near miss by default
+const NEAR_MISS_THRESHOLD: f64 = 0.9;
+
+/// Text that is hashed and compared by its normalized form, keeping the original text to display it
+///
+/// The key joins the words with a single space and is normalized with [`Normalizer::new`] unless
+/// created with [`NormKey::with_normalizer`], so it ignores case, accents and repeated spaces.
+///
+/// # Examples
+///
+/// ```
+/// use strings::NormKey;
+///
+/// let key = NormKey::new(" Universidad  Anáhuac ");
+///
+/// assert_eq!(key, NormKey::new("UNIVERSIDAD ANAHUAC"));
+/// assert_eq!(key.key(), "universidad anahuac");
+/// assert_eq!(key.to_string(), " Universidad  Anáhuac ");
+/// ```
+#[derive(Debug, Clone)]
+pub struct NormKey {
+    original: String,
+    key: String,
+}
+
+impl NormKey {
+    #[must_use]
+    pub fn new(text: impl Into<String>) -> Self {
+        Self::with_normalizer(text, Normalizer::new())
+    }
+
+    #[must_use]
+    pub fn with_normalizer(text: impl Into<String>, normalizer: Normalizer) -> Self {
+        let original = text.into();
+        let key = normalizer.sanitize(&original, &[]).collect();
+
+        Self { original, key }
+    }
+
+    /// The text as it was written
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        &self.original
+    }
+
+    /// The normalized text used to compare
+    #[must_use]
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    #[must_use]
+    pub fn into_string(self) -> String {
+        self.original
+    }
+}
+
+impl PartialEq for NormKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.key == other.key
+    }
+}
+
+impl Eq for NormKey {}
+
+impl PartialOrd for NormKey {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for NormKey {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.key.cmp(&other.key)
+    }
+}
+
+impl Hash for NormKey {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.key.hash(state);
+    }
+}
+
+/// Borrows the normalized key, so a map can be queried with an already normalized `&str`
+impl Borrow<str> for NormKey {
+    fn borrow(&self) -> &str {
+        &self.key
+    }
+}
+
+impl fmt::Display for NormKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.original.fmt(f)
+    }
+}
+
+impl From<&str> for NormKey {
+    fn from(text: &str) -> Self {
+        Self::new(text)
+    }
+}
+
+impl From<String> for NormKey {
+    fn from(text: String) -> Self {
+        Self::new(text)
+    }
+}
+
+/// Result of [`NormMap::lookup`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Lookup<'a, V> {
+    /// A key with the same normalized form
+    Found(&'a NormKey, &'a V),
+    /// No key with the same normalized form, but the most similar one is above the threshold
+    NearMiss {
+        key: &'a NormKey,
+        value: &'a V,
+        similarity: f64,
+    },
+    Missing,
+}
+
+impl<'a, V> Lookup<'a, V> {
+    /// The value of an exact match
+    #[must_use]
+    pub fn found(self) -> Option<&'a V> {
+        match self {
+            Lookup::Found(_, value) => Some(value),
+            _ => None,
+        }
+    }
+}
+
+/// Map with [`NormKey`] keys that is queried with the text as written
+///
+/// # Examples
+///
+/// ```
+/// use strings::{Lookup, NormMap};
+///
+/// let mut map = NormMap::new();
+/// map.insert("Maestría", true);
+///
+/// assert_eq!(map.get("MAESTRIA "), Some(&true));
+/// assert!(matches!(map.lookup("Maestria en"), Lookup::NearMiss { .. }));
+/// assert!(matches!(map.lookup("Doctorado"), Lookup::Missing));
+/// ```
+#[derive(Debug, Clone)]
+pub struct NormMap<V> {
+    normalizer: Normalizer,
+    near_miss: f64,
+    entries: HashMap<NormKey, V>,
+}
+
+impl<V> Default for NormMap<V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<V> NormMap<V> {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::with_normalizer(Normalizer::new())
+    }
+
+    /// Map whose keys are normalized with `normalizer`
+    #[must_use]
+    pub fn with_normalizer(normalizer: Normalizer) -> Self {
+        Self {
+            normalizer,
+            near_miss: NEAR_MISS_THRESHOLD,
+            entries: HashMap::new(),
+        }
+    }
+
+    /// Minimum [`jaro_winkler`] similarity of the normalized keys to report a near miss in
+    /// [`NormMap::lookup`], 0.9 by default
+    #[must_use]
+    pub fn near_miss_threshold(mut self, threshold: f64) -> Self {
+        self.near_miss = threshold;
+        self
+    }
+
+    /// Insert `value` for `key`, returning the previous value. If the normalized key was present,
+    /// the original text of the first key is kept
+    pub fn insert(&mut self, key: impl Into<String>, value: V) -> Option<V> {
+        let key = self.key(key);
+        self.entries.insert(key, value)
+    }
+
+    #[must_use]
+    pub fn get(&self, key: &str) -> Option<&V> {
+        self.get_key_value(key).map(|(_, value)| value)
+    }
+
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut V> {
+        let key = self.key(key);
+        self.entries.get_mut(key.key())
+    }
+
+    #[must_use]
+    pub fn get_key_value(&self, key: &str) -> Option<(&NormKey, &V)> {
+        let key = self.key(key);
+        self.entries.get_key_value(key.key())
+    }
+
+    #[must_use]
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.get_key_value(key).is_some()
+    }
+
+    /// Like [`NormMap::get`] but reporting the most similar key if there is no exact match
+    #[must_use]
+    pub fn lookup(&self, key: &str) -> Lookup<'_, V> {
+        let key = self.key(key);
+        if let Some((key, value)) = self.entries.get_key_value(key.key()) {
+            return Lookup::Found(key, value);
+        }
+
+        self.entries
+            .iter()
+            .map(|(candidate, value)| (candidate, value, jaro_winkler(key.key(), candidate.key())))
+            .filter(|(_, _, similarity)| *similarity >= self.near_miss)
+            .max_by(|a, b| a.2.total_cmp(&b.2))
+            .map_or(Lookup::Missing, |(key, value, similarity)| {
+                Lookup::NearMiss {
+                    key,
+                    value,
+                    similarity,
+                }
+            })
+    }
+
+    pub fn remove(&mut self, key: &str) -> Option<V> {
+        let key = self.key(key);
+        self.entries.remove(key.key())
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&NormKey, &V)> {
+        self.entries.iter()
+    }
+
+    fn key(&self, text: impl Into<String>) -> NormKey {
+        NormKey::with_normalizer(text, self.normalizer)
+    }
+}
+
+impl<K: Into<String>, V> FromIterator<(K, V)> for NormMap<V> {
+    fn from_iter<T: IntoIterator<Item = (K, V)>>(iter: T) -> Self {
+        let mut map = Self::new();
+        map.extend(iter);
+        map
+    }
+}
+
+impl<K: Into<String>, V> Extend<(K, V)> for NormMap<V> {
+    fn extend<T: IntoIterator<Item = (K, V)>>(&mut self, iter: T) {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keys() {
+        let a = NormKey::new("Peña  Ávila");
+        let b = NormKey::new("PENA AVILA\u{200B}");
+
+        assert_eq!(a, b);
+        assert_eq!(a.as_str(), "Peña  Ávila");
+        assert_ne!(
+            a,
+            NormKey::with_normalizer("Peña Ávila", Normalizer::new().keep_enye(true))
+        );
+    }
+
+    #[test]
+    fn map() {
+        let mut map = ["Licenciatura", "Maestría"]
+            .into_iter()
+            .zip([1, 2])
+            .collect::<NormMap<_>>();
+
+        assert_eq!(map.insert("MAESTRIA", 3), Some(2));
+        assert_eq!(
+            map.get_key_value("maestría").map(|(k, v)| (k.as_str(), *v)),
+            Some(("Maestría", 3))
+        );
+        assert_eq!(map.len(), 2);
+
+        match map.lookup("Licenciatra") {
+            Lookup::NearMiss {
+                key,
+                value,
+                similarity,
+            } => {
+                assert_eq!((key.as_str(), *value), ("Licenciatura", 1));
+                assert!(similarity < 1.);
+            }
+            other => panic!("expected a near miss, got {other:?}"),
+        }
+        assert_eq!(map.lookup("licenciatura").found(), Some(&1));
+
+        let strict = map.clone().near_miss_threshold(1.);
+        assert_eq!(strict.lookup("Licenciatra"), Lookup::Missing);
+        assert_eq!(map.remove(" licenciatura "), Some(1));
+        assert!(!map.contains_key("Licenciatura"));
+    }
+}
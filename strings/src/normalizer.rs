@@ -0,0 +1,210 @@
+use caseless::Caseless;
+use itertools::Either;
+use unicode_normalization::{char::is_combining_mark, UnicodeNormalization};
+
+use crate::{get_words_ext_exact, is_invisible, space_join, split_words};
+
+/// Configurable text normalization used to build comparable keys
+///
+/// By default it applies full Unicode case folding and removes all combining marks, so `"Ñandú"`
+/// becomes `"nandu"`. The Spanish `ñ` and `ü` may be preserved with [`Normalizer::keep_enye`] and
+/// [`Normalizer::keep_diaeresis`]. Invisible formatting chars are removed and every Unicode
+/// whitespace separates words unless [`Normalizer::exact_spaces`] is set.
+///
+/// # Examples
+///
+/// ```
+/// use strings::Normalizer;
+///
+/// assert_eq!(Normalizer::new().normalize("ÉRIKA Núñez"), "erika nunez");
+/// assert_eq!(Normalizer::new().keep_enye(true).normalize("ÉRIKA Núñez"), "erika nuñez");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[allow(clippy::struct_excessive_bools)]
+pub struct Normalizer {
+    fold_case: bool,
+    strip_marks: bool,
+    keep_enye: bool,
+    keep_diaeresis: bool,
+    exact_spaces: bool,
+}
+
+impl Default for Normalizer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Normalizer {
+    /// Normalizer that folds case and removes every combining mark
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            fold_case: true,
+            strip_marks: true,
+            keep_enye: false,
+            keep_diaeresis: false,
+            exact_spaces: false,
+        }
+    }
+
+    /// Apply full Unicode case folding (`"ß"` becomes `"ss"`, `"Ñ"` becomes `"ñ"`)
+    #[must_use]
+    pub const fn fold_case(mut self, fold_case: bool) -> Self {
+        self.fold_case = fold_case;
+        self
+    }
+
+    /// Remove all combining marks (accents, tildes, diaeresis, etc.)
+    #[must_use]
+    pub const fn strip_marks(mut self, strip_marks: bool) -> Self {
+        self.strip_marks = strip_marks;
+        self
+    }
+
+    /// Keep the tilde of `ñ` and `Ñ` even when stripping marks
+    #[must_use]
+    pub const fn keep_enye(mut self, keep_enye: bool) -> Self {
+        self.keep_enye = keep_enye;
+        self
+    }
+
+    /// Keep the diaeresis of `ü` and `Ü` even when stripping marks
+    #[must_use]
+    pub const fn keep_diaeresis(mut self, keep_diaeresis: bool) -> Self {
+        self.keep_diaeresis = keep_diaeresis;
+        self
+    }
+
+    /// Only split words on `' '`, `'\n'`, `'\t'` and `'\r'` and keep the invisible formatting chars,
+    /// like [`crate::get_words_exact`]
+    #[must_use]
+    pub const fn exact_spaces(mut self, exact_spaces: bool) -> Self {
+        self.exact_spaces = exact_spaces;
+        self
+    }
+
+    fn keeps_marks(self, c: char) -> bool {
+        !self.strip_marks
+            || (self.keep_enye && matches!(c, 'ñ' | 'Ñ'))
+            || (self.keep_diaeresis && matches!(c, 'ü' | 'Ü'))
+    }
+
+    /// Normalize a stream of chars. The result is in NFC form
+    pub fn chars<'a>(
+        self,
+        chars: impl Iterator<Item = char> + 'a,
+    ) -> impl Iterator<Item = char> + 'a {
+        let chars = chars
+            .filter(move |c| self.exact_spaces || !is_invisible(*c))
+            .nfc();
+        let chars = if self.fold_case {
+            Either::Left(chars.default_case_fold())
+        } else {
+            Either::Right(chars)
+        };
+
+        chars
+            .flat_map(move |c| {
+                let keep = self.keeps_marks(c);
+                c.nfd().filter(move |d| keep || !is_combining_mark(*d))
+            })
+            .nfc()
+    }
+
+    /// Like [`crate::sanitize`] but using this normalizer: joins the words of `contents` with a
+    /// single space, splitting also on `seps`, and normalizes the result
+    pub fn sanitize<'a>(
+        self,
+        contents: &'a str,
+        seps: &'a [char],
+    ) -> impl Iterator<Item = char> + 'a {
+        let words = if self.exact_spaces {
+            Either::Left(get_words_ext_exact(contents, seps))
+        } else {
+            Either::Right(split_words(contents, seps))
+        };
+
+        self.chars(space_join(words).flat_map(str::chars))
+    }
+
+    /// Normalize `text` into a new `String`
+    #[must_use]
+    pub fn normalize(self, text: &str) -> String {
+        let mut buf = String::with_capacity(text.len());
+        self.normalize_into(text, &mut buf);
+        buf
+    }
+
+    /// Normalize `text` into `buf`, clearing it first, so the allocation may be reused between calls
+    pub fn normalize_into(self, text: &str, buf: &mut String) {
+        buf.clear();
+        buf.extend(self.chars(text.chars()));
+    }
+
+    /// Sanitize `contents` into `buf`, clearing it first, so the allocation may be reused between calls
+    pub fn sanitize_into(self, contents: &str, seps: &[char], buf: &mut String) {
+        buf.clear();
+        buf.extend(self.sanitize(contents, seps));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn folds_non_ascii() {
+        assert_eq!(Normalizer::new().normalize("ÑÉÁ"), "nea");
+        assert_eq!(Normalizer::new().strip_marks(false).normalize("ÑÉÁ"), "ñéá");
+        assert_eq!(Normalizer::new().normalize("Straße"), "strasse");
+    }
+
+    #[test]
+    fn keeps_spanish_letters() {
+        let normalizer = Normalizer::new().keep_enye(true).keep_diaeresis(true);
+
+        assert_eq!(normalizer.normalize("PEÑA Güémez"), "peña güemez");
+        // Decomposed input is composed before deciding what to keep
+        assert_eq!(normalizer.normalize("Pen\u{303}a"), "peña");
+    }
+
+    #[test]
+    fn removes_all_marks() {
+        assert_eq!(Normalizer::new().normalize("a\u{301}\u{308}o\u{30c}"), "ao");
+        assert_eq!(
+            Normalizer::new().fold_case(false).normalize("José MARÍA"),
+            "Jose MARIA"
+        );
+    }
+
+    #[test]
+    fn exact_spaces() {
+        let text = "Pe\u{AD}ña\u{A0}Ávila";
+
+        assert_eq!(Normalizer::new().normalize(text), "pena\u{A0}avila");
+        assert_eq!(
+            Normalizer::new().sanitize(text, &[]).collect::<String>(),
+            "pena avila"
+        );
+        assert_eq!(
+            Normalizer::new()
+                .exact_spaces(true)
+                .sanitize(text, &[])
+                .collect::<String>(),
+            "pe\u{AD}na\u{A0}avila"
+        );
+    }
+
+    #[test]
+    fn reuses_buffer() {
+        let mut buf = String::new();
+        let normalizer = Normalizer::new();
+
+        normalizer.sanitize_into("  Núñez,\t Peña ", &[','], &mut buf);
+        assert_eq!(buf, "nunez pena");
+
+        normalizer.normalize_into("Ávila", &mut buf);
+        assert_eq!(buf, "avila");
+    }
+}
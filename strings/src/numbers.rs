@@ -0,0 +1,291 @@
+//! Parsing of numbers written as text with local conventions, like `"1,234.50"`, `"$ 12 000"`,
+//! `"85%"` or `"1.234,5"`
+//!
+//! ```
+//! use strings::numbers::{Number, NumberError, NumberFormat};
+//!
+//! let format = NumberFormat::new();
+//!
+//! assert_eq!(format.parse("$ 12 000"), Ok(Number::Int(12_000)));
+//! assert_eq!(format.parse("1,234.50"), Ok(Number::Float(1234.5)));
+//! assert_eq!(format.parse("85%"), Ok(Number::Float(0.85)));
+//! assert_eq!(NumberFormat::comma_decimal().parse("1.234,5"), Ok(Number::Float(1234.5)));
+//! assert!(matches!(format.parse("#DIV/0!"), Err(NumberError::Spreadsheet(_))));
+//! ```
+
+use thiserror::Error;
+
+/// Errors that spreadsheets write in cells whose formula failed
+const SPREADSHEET_ERRORS: &[&str] = &[
+    "#DIV/0!",
+    "#N/A",
+    "#NAME?",
+    "#NULL!",
+    "#NUM!",
+    "#REF!",
+    "#VALUE!",
+    "#¡DIV/0!",
+    "#N/D",
+    "#¿NOMBRE?",
+    "#¡NULO!",
+    "#¡NUM!",
+    "#¡REF!",
+    "#¡VALOR!",
+];
+
+/// Currency symbols and codes accepted before or after the number
+const CURRENCIES: &[&str] = &["US$", "MXN", "USD", "EUR", "$", "€"];
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum NumberError {
+    #[error("the text is empty")]
+    Empty,
+    #[error("the cell has the spreadsheet error `{0}`")]
+    Spreadsheet(String),
+    #[error("`{0}` is not a number")]
+    Invalid(String),
+}
+
+/// A parsed number, integers are kept exact
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Number {
+    Int(i64),
+    Float(f64),
+}
+
+impl Number {
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn as_f64(self) -> f64 {
+        match self {
+            Number::Int(v) => v as f64,
+            Number::Float(v) => v,
+        }
+    }
+}
+
+/// Conventions used to write numbers as text
+///
+/// By default the decimal separator is `'.'` and thousands are grouped with `','`, currency
+/// symbols like `$` are ignored and percentages are read as fractions, so `"85%"` is `0.85`.
+/// Thousands may also be grouped with any Unicode whitespace, but groups after the first must have
+/// three digits, so a number written with another convention is an error instead of a wrong value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NumberFormat {
+    decimal: char,
+    thousands: char,
+    currency: bool,
+    percent_as_fraction: bool,
+}
+
+impl Default for NumberFormat {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl NumberFormat {
+    /// Format with `'.'` as decimal separator and `','` for thousands, like `"1,234.5"`
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            decimal: '.',
+            thousands: ',',
+            currency: true,
+            percent_as_fraction: true,
+        }
+    }
+
+    /// Format with `','` as decimal separator and `'.'` for thousands, like `"1.234,5"`
+    #[must_use]
+    pub const fn comma_decimal() -> Self {
+        Self::new().decimal(',').thousands('.')
+    }
+
+    #[must_use]
+    pub const fn decimal(mut self, decimal: char) -> Self {
+        self.decimal = decimal;
+        self
+    }
+
+    #[must_use]
+    pub const fn thousands(mut self, thousands: char) -> Self {
+        self.thousands = thousands;
+        self
+    }
+
+    /// Ignore a currency symbol or code like `$`, `€` or `MXN` before or after the number
+    #[must_use]
+    pub const fn currency(mut self, currency: bool) -> Self {
+        self.currency = currency;
+        self
+    }
+
+    /// Divide by 100 the numbers that end with `%`, otherwise only the `%` is removed
+    #[must_use]
+    pub const fn percent_as_fraction(mut self, percent_as_fraction: bool) -> Self {
+        self.percent_as_fraction = percent_as_fraction;
+        self
+    }
+
+    /// Parse `text` as a number. Negative numbers may be written with `-` or between parenthesis
+    /// as in accounting, numbers with decimals or percentages are always [`Number::Float`]
+    pub fn parse(&self, text: &str) -> Result<Number, NumberError> {
+        let invalid = || NumberError::Invalid(text.to_string());
+        let mut rest = text.trim_matches(|c: char| c.is_whitespace() || crate::is_invisible(c));
+
+        if rest.is_empty() {
+            return Err(NumberError::Empty);
+        }
+        if let Some(error) = SPREADSHEET_ERRORS
+            .iter()
+            .find(|error| rest.eq_ignore_ascii_case(error))
+        {
+            return Err(NumberError::Spreadsheet((*error).to_string()));
+        }
+
+        let mut negative = false;
+        let mut percent = false;
+        if let Some(inner) = rest.strip_suffix('%') {
+            percent = true;
+            rest = inner.trim_end();
+        }
+
+        for _ in 0..2 {
+            rest = self.strip_currency(rest);
+            if let Some(inner) = rest.strip_prefix('(').and_then(|r| r.strip_suffix(')')) {
+                negative = !negative;
+                rest = inner.trim();
+            } else if let Some(inner) = rest.strip_prefix('-') {
+                negative = !negative;
+                rest = inner.trim_start();
+            } else if let Some(inner) = rest.strip_prefix('+') {
+                rest = inner.trim_start();
+            }
+        }
+
+        let (int, frac) = match rest.split_once(self.decimal) {
+            Some((int, frac)) => (int, Some(frac)),
+            None => (rest, None),
+        };
+        let int = self.group_digits(int).ok_or_else(invalid)?;
+        let frac = match frac {
+            Some(frac) if frac.bytes().all(|b| b.is_ascii_digit()) => Some(frac),
+            Some(_) => return Err(invalid()),
+            None => None,
+        };
+
+        if int.is_empty() && frac.unwrap_or_default().is_empty() {
+            return Err(invalid());
+        }
+
+        let sign = if negative { "-" } else { "" };
+        if frac.is_none() && !percent {
+            if let Ok(v) = format!("{sign}{int}").parse() {
+                return Ok(Number::Int(v));
+            }
+        }
+
+        let value = format!("{sign}{int}.{}", frac.unwrap_or_default())
+            .trim_end_matches('.')
+            .parse::<f64>()
+            .map_err(|_| invalid())?;
+
+        Ok(Number::Float(if percent && self.percent_as_fraction {
+            value / 100.
+        } else {
+            value
+        }))
+    }
+
+    fn strip_currency<'a>(&self, text: &'a str) -> &'a str {
+        if !self.currency {
+            return text;
+        }
+
+        CURRENCIES
+            .iter()
+            .find_map(|currency| {
+                let len = currency.len();
+                let prefix = text.get(..len).filter(|p| p.eq_ignore_ascii_case(currency));
+                let suffix = text
+                    .len()
+                    .checked_sub(len)
+                    .and_then(|start| text.get(start..))
+                    .filter(|s| s.eq_ignore_ascii_case(currency));
+
+                match (prefix, suffix) {
+                    (Some(_), _) => Some(&text[len..]),
+                    (None, Some(_)) => Some(&text[..text.len() - len]),
+                    (None, None) => None,
+                }
+            })
+            .map_or(text, str::trim)
+    }
+
+    /// Digits of the integer part without the thousands separators, `None` if the groups are not
+    /// valid
+    fn group_digits(&self, int: &str) -> Option<String> {
+        let groups = int
+            .split(|c: char| c == self.thousands || c.is_whitespace())
+            .collect::<Vec<_>>();
+
+        let valid = groups.iter().enumerate().all(|(i, group)| {
+            let len_ok = match (i, groups.len()) {
+                (_, 1) => true,
+                (0, _) => (1..=3).contains(&group.len()),
+                _ => group.len() == 3,
+            };
+            len_ok && group.bytes().all(|b| b.is_ascii_digit())
+        });
+
+        valid.then(|| groups.concat())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        let format = NumberFormat::new();
+
+        assert_eq!(format.parse(" 1,234.50 "), Ok(Number::Float(1234.5)));
+        assert_eq!(format.parse("$ 12 000"), Ok(Number::Int(12_000)));
+        assert_eq!(format.parse("-$1,000"), Ok(Number::Int(-1000)));
+        assert_eq!(format.parse("(1,000.00) MXN"), Ok(Number::Float(-1000.)));
+        assert_eq!(format.parse(".5"), Ok(Number::Float(0.5)));
+        assert_eq!(format.parse("12\u{A0}345"), Ok(Number::Int(12_345)));
+        assert_eq!(format.parse("85 %"), Ok(Number::Float(0.85)));
+        assert_eq!(
+            format.percent_as_fraction(false).parse("85%"),
+            Ok(Number::Float(85.))
+        );
+    }
+
+    #[test]
+    fn errors() {
+        let format = NumberFormat::new();
+
+        assert_eq!(format.parse("  "), Err(NumberError::Empty));
+        assert_eq!(
+            format.parse("#div/0!"),
+            Err(NumberError::Spreadsheet("#DIV/0!".to_string()))
+        );
+        assert!(format.parse("1.234,5").is_err());
+        assert!(format.parse("12,34").is_err());
+        assert!(format.parse("abc").is_err());
+        assert!(format.parse("$").is_err());
+        assert!(format.currency(false).parse("$5").is_err());
+    }
+
+    #[test]
+    fn comma_decimal() {
+        let format = NumberFormat::comma_decimal();
+
+        assert_eq!(format.parse("1.234,5"), Ok(Number::Float(1234.5)));
+        assert_eq!(format.parse("1.234"), Ok(Number::Int(1234)));
+        assert_eq!(format.parse("12,5 %"), Ok(Number::Float(0.125)));
+    }
+}
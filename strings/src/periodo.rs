@@ -0,0 +1,315 @@
+//! Academic periods written in many forms, like `"Ciclo escolar 2022-2023"`, `"20-21"`,
+//! `"ago dic 23"` or `"ene-jul 2024"`
+//!
+//! ```
+//! use strings::periodo::{Period, Semester};
+//!
+//! let cycle = Period::find("Ciclo escolar 2022-2023 (preliminar)").unwrap();
+//! assert_eq!(cycle, "22-23".parse().unwrap());
+//! assert_eq!(cycle.to_string(), "2022-2023");
+//!
+//! let term = Period::find("titulares ago dic 23.xlsx").unwrap();
+//! assert_eq!(term.semester(), Some(Semester::Second));
+//! assert_eq!(term.to_string(), "ago-dic 2023");
+//! assert!(cycle < term);
+//! ```
+
+use std::{fmt, str::FromStr};
+
+use thiserror::Error;
+
+use crate::Normalizer;
+
+/// Short names of the months, used to format the periods
+const MONTHS: [&str; 12] = [
+    "ene", "feb", "mar", "abr", "may", "jun", "jul", "ago", "sep", "oct", "nov", "dic",
+];
+
+/// Full names of the months, any prefix with at least three letters is accepted
+const MONTH_NAMES: [&str; 12] = [
+    "enero",
+    "febrero",
+    "marzo",
+    "abril",
+    "mayo",
+    "junio",
+    "julio",
+    "agosto",
+    "septiembre",
+    "octubre",
+    "noviembre",
+    "diciembre",
+];
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[error("`{0}` is not an academic period")]
+pub struct ParsePeriodError(String);
+
+/// Half of the year in which a period with months starts
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Semester {
+    /// Starts between January and June
+    First,
+    /// Starts between July and December
+    Second,
+}
+
+/// An academic period: a school cycle that spans two years, like `2022-2023`, or a range of months,
+/// like `ago-dic 2023`
+///
+/// Periods are ordered by their start year, then by their end year and then by their months.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Period {
+    start: u16,
+    end: u16,
+    /// First and last month, from 1 to 12
+    months: Option<(u8, u8)>,
+}
+
+impl Period {
+    /// School cycle from `start` to the next year
+    #[must_use]
+    pub const fn cycle(start: u16) -> Self {
+        Self {
+            start,
+            end: start + 1,
+            months: None,
+        }
+    }
+
+    /// Months from `from` to `to`, numbered from 1 to 12, starting in `year`. If `to` is before
+    /// `from` the period ends the next year. `None` if a month is not valid
+    #[must_use]
+    pub fn months(year: u16, from: u8, to: u8) -> Option<Self> {
+        let valid = 1..=12;
+        if !valid.contains(&from) || !valid.contains(&to) {
+            return None;
+        }
+
+        Some(Self {
+            start: year,
+            end: year + u16::from(to < from),
+            months: Some((from, to)),
+        })
+    }
+
+    /// Find the first period written in `text`, ignoring the words around it
+    #[must_use]
+    pub fn find(text: &str) -> Option<Self> {
+        let text = Normalizer::new().normalize(text);
+        let tokens = tokens(&text);
+
+        (0..tokens.len()).find_map(|i| parse_at(&tokens[i..]).map(|(period, _)| period))
+    }
+
+    #[must_use]
+    pub fn start_year(&self) -> u16 {
+        self.start
+    }
+
+    #[must_use]
+    pub fn end_year(&self) -> u16 {
+        self.end
+    }
+
+    /// First and last month, from 1 to 12, `None` for school cycles
+    #[must_use]
+    pub fn month_range(&self) -> Option<(u8, u8)> {
+        self.months
+    }
+
+    /// Semester of a period with months, `None` for school cycles
+    #[must_use]
+    pub fn semester(&self) -> Option<Semester> {
+        self.months.map(|(from, _)| {
+            if from <= 6 {
+                Semester::First
+            } else {
+                Semester::Second
+            }
+        })
+    }
+
+    /// Format with two digit years, like `"22-23"` or `"ago-dic 23"`
+    #[must_use]
+    pub fn short(&self) -> String {
+        let (start, end) = (self.start % 100, self.end % 100);
+        match self.months {
+            None => format!("{start:02}-{end:02}"),
+            Some((from, to)) if self.start == self.end => {
+                format!("{} {start:02}", month_span(from, to))
+            }
+            Some((from, to)) => format!("{} {start:02}-{} {end:02}", month(from), month(to)),
+        }
+    }
+}
+
+impl fmt::Display for Period {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.months {
+            None => write!(f, "{}-{}", self.start, self.end),
+            Some((from, to)) if self.start == self.end => {
+                write!(f, "{} {}", month_span(from, to), self.start)
+            }
+            Some((from, to)) => write!(
+                f,
+                "{} {}-{} {}",
+                month(from),
+                self.start,
+                month(to),
+                self.end
+            ),
+        }
+    }
+}
+
+impl FromStr for Period {
+    type Err = ParsePeriodError;
+
+    /// Parse `s` as a single period, use [`Period::find`] to look for it in a longer text
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let text = Normalizer::new().normalize(s);
+        let tokens = tokens(&text);
+
+        match parse_at(&tokens) {
+            Some((period, used)) if used == tokens.len() => Ok(period),
+            _ => Err(ParsePeriodError(s.to_string())),
+        }
+    }
+}
+
+fn tokens(text: &str) -> Vec<&str> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|t| !t.is_empty())
+        .collect()
+}
+
+/// Period at the start of `tokens` and the number of tokens it uses
+fn parse_at(tokens: &[&str]) -> Option<(Period, usize)> {
+    match tokens {
+        [from, to, year, ..] if parse_month(from).is_some() && parse_month(to).is_some() => {
+            let year = parse_year(year, None)?;
+            Some((
+                Period::months(year, parse_month(from)?, parse_month(to)?)?,
+                3,
+            ))
+        }
+        [from, year, ..] if parse_month(from).is_some() => {
+            let (month, year) = (parse_month(from)?, parse_year(year, None)?);
+            Some((Period::months(year, month, month)?, 2))
+        }
+        [start, end, ..] => {
+            let start = parse_year(start, None)?;
+            let end = parse_year(end, Some(start))?;
+            (end == start + 1).then_some((Period::cycle(start), 2))
+        }
+        _ => None,
+    }
+}
+
+/// Month from 1 to 12 of a lowercase name without accents
+fn parse_month(token: &str) -> Option<u8> {
+    if token.len() < 3 {
+        return None;
+    }
+
+    let token = if token.starts_with("set") {
+        token.replacen("set", "sept", 1)
+    } else {
+        token.to_string()
+    };
+
+    MONTH_NAMES
+        .iter()
+        .position(|name| name.starts_with(&token))
+        .and_then(|i| u8::try_from(i + 1).ok())
+}
+
+/// Year with four digits, or with two digits in the century of `base` (or 2000 if there is no
+/// `base`). A two digit year before `base` is in the next century, so `"00"` after 1999 is 2000
+fn parse_year(token: &str, base: Option<u16>) -> Option<u16> {
+    if !token.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+
+    let year = token.parse::<u16>().ok()?;
+    match token.len() {
+        4 => Some(year),
+        2 => {
+            let year = base.map_or(2000, |base| base / 100 * 100) + year;
+            Some(if base.is_some_and(|base| year < base) {
+                year + 100
+            } else {
+                year
+            })
+        }
+        _ => None,
+    }
+}
+
+fn month(n: u8) -> &'static str {
+    MONTHS[usize::from(n - 1)]
+}
+
+fn month_span(from: u8, to: u8) -> String {
+    if from == to {
+        month(from).to_string()
+    } else {
+        format!("{}-{}", month(from), month(to))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cycles() {
+        let cycle = Period::cycle(2022);
+
+        for text in ["2022-2023", "22-23", "2022-23", "2022 2023"] {
+            assert_eq!(text.parse(), Ok(cycle), "{text}");
+        }
+        assert_eq!(Period::find("Ciclo escolar 2022-2023"), Some(cycle));
+        assert_eq!(
+            Period::find("Cuota mercado 20-21"),
+            Some(Period::cycle(2020))
+        );
+        assert_eq!(cycle.short(), "22-23");
+        assert!("2022-2024".parse::<Period>().is_err());
+        assert_eq!("1999-00".parse(), Ok(Period::cycle(1999)));
+        assert_eq!("99-00".parse(), Ok(Period::cycle(2099)));
+        assert!("Ciclo 2022-2023".parse::<Period>().is_err());
+        assert_eq!(Period::find("Reporte 2023"), None);
+    }
+
+    #[test]
+    fn months() {
+        let term = "ene jul 24".parse::<Period>().expect("valid period");
+
+        assert_eq!(term.month_range(), Some((1, 7)));
+        assert_eq!(term.semester(), Some(Semester::First));
+        assert_eq!(term.to_string(), "ene-jul 2024");
+        assert_eq!(term.short(), "ene-jul 24");
+        assert_eq!("Enero-Julio 2024".parse(), Ok(term));
+        assert_eq!("Septiembre 2023".parse().ok(), Period::months(2023, 9, 9));
+        assert_eq!("set 2023".parse().ok(), Period::months(2023, 9, 9));
+
+        let crossing = Period::months(2023, 8, 1).expect("valid months");
+        assert_eq!(crossing.end_year(), 2024);
+        assert_eq!(crossing.to_string(), "ago 2023-ene 2024");
+        assert_eq!(Period::months(2023, 0, 13), None);
+    }
+
+    #[test]
+    fn ordering() {
+        let mut periods = ["ago dic 23", "22-23", "ene jul 24", "ene jul 23", "23-24"]
+            .map(|text| text.parse::<Period>().expect("valid period"));
+        periods.sort();
+
+        assert_eq!(
+            periods.map(|period| period.short()),
+            ["22-23", "ene-jul 23", "ago-dic 23", "23-24", "ene-jul 24"]
+        );
+    }
+}
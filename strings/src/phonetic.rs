@@ -0,0 +1,179 @@
+use std::collections::VecDeque;
+
+use crate::{sanitize_spaces_iter, Normalizer};
+
+/// Spanish phonetic code of the words, sounds that are written differently but pronounced the same
+/// get the same code, so it may be used as a blocking key when matching names
+///
+/// The rules are: `b`/`v`/`w` sound the same, `s`/`z` and `c` before `e`/`i` sound as `s`, `c`
+/// before other letters, `k` and `qu` sound as `k`, `g` before `e`/`i` and `j` sound as `j`, `ll`
+/// and `y` sound the same, `h` is silent, `x` at the start of a word or before a vowel sounds as
+/// `j`, and repeated sounds are written once. A `y` that ends a word is a vowel.
+///
+/// # Examples
+///
+/// ```
+/// use strings::{phonetic, sanitize_spaces_iter};
+///
+/// let code = |text| phonetic(sanitize_spaces_iter(text)).collect::<String>();
+///
+/// assert_eq!(code("Gonzales"), code("González"));
+/// assert_eq!(code("Ximena Velázquez"), code("Jimena Velasquez"));
+/// ```
+pub fn phonetic<'a>(words: impl Iterator<Item = &'a str> + 'a) -> impl Iterator<Item = char> + 'a {
+    phonetic_chars(words.flat_map(str::chars))
+}
+
+/// Spanish phonetic code of a stream of chars, see [`phonetic`]
+pub fn phonetic_chars<'a>(
+    chars: impl Iterator<Item = char> + 'a,
+) -> impl Iterator<Item = char> + 'a {
+    let normalizer = Normalizer::new().keep_enye(true).keep_diaeresis(true);
+    Phonetic::new(normalizer.chars(chars))
+}
+
+/// Spanish phonetic code of `text` with a single space between the code of each word
+#[must_use]
+pub fn phonetic_key(text: &str) -> String {
+    phonetic(sanitize_spaces_iter(text)).collect()
+}
+
+/// Iterator that encodes folded chars, see [`phonetic`]
+struct Phonetic<I: Iterator<Item = char>> {
+    inner: I,
+    ahead: [Option<char>; 2],
+    queue: VecDeque<char>,
+    last: Option<char>,
+    word_start: bool,
+    space_due: bool,
+}
+
+impl<I: Iterator<Item = char>> Phonetic<I> {
+    fn new(mut inner: I) -> Self {
+        let ahead = [inner.next(), inner.next()];
+
+        Self {
+            inner,
+            ahead,
+            queue: VecDeque::with_capacity(2),
+            last: None,
+            word_start: true,
+            space_due: false,
+        }
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.ahead[0];
+        self.ahead = [self.ahead[1], self.inner.next()];
+        c
+    }
+
+    fn emit(&mut self, code: char) {
+        if self.space_due {
+            self.queue.push_back(' ');
+            self.space_due = false;
+        } else if self.last == Some(code) {
+            return;
+        }
+
+        self.queue.push_back(code);
+        self.last = Some(code);
+    }
+
+    fn encode(&mut self, c: char, word_start: bool) {
+        let next = self.ahead[0];
+        let front_vowel = |c: Option<char>| matches!(c, Some('e' | 'i'));
+
+        match c {
+            'a' | 'e' | 'i' | 'o' => self.emit(c),
+            'u' | 'ü' => self.emit('u'),
+            'y' if !next.is_some_and(char::is_alphabetic) => self.emit('i'),
+            'b' | 'v' | 'w' => self.emit('b'),
+            'c' if next == Some('h') => {
+                self.bump();
+                self.emit('c');
+            }
+            'c' if front_vowel(next) => self.emit('s'),
+            'c' | 'k' => self.emit('k'),
+            's' | 'z' => self.emit('s'),
+            'q' => {
+                if next == Some('u') && front_vowel(self.ahead[1]) {
+                    self.bump();
+                }
+                self.emit('k');
+            }
+            'g' if front_vowel(next) => self.emit('j'),
+            'g' => {
+                if next == Some('u') && front_vowel(self.ahead[1]) {
+                    self.bump();
+                }
+                self.emit('g');
+            }
+            'x' if word_start || next.is_some_and(|n| "aeiou".contains(n)) => self.emit('j'),
+            'x' => {
+                self.emit('k');
+                self.emit('s');
+            }
+            'l' if next == Some('l') => {
+                self.bump();
+                self.emit('y');
+            }
+            'ñ' => {
+                self.emit('n');
+                self.emit('y');
+            }
+            // The h is silent
+            'h' => {}
+            c if c.is_ascii_alphabetic() => self.emit(c),
+            _ => {}
+        }
+    }
+}
+
+impl<I: Iterator<Item = char>> Iterator for Phonetic<I> {
+    type Item = char;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.queue.is_empty() {
+            let c = self.bump()?;
+
+            if c.is_whitespace() {
+                self.word_start = true;
+                self.space_due = self.last.is_some();
+                continue;
+            }
+
+            let word_start = std::mem::replace(&mut self.word_start, false);
+            self.encode(c, word_start);
+        }
+
+        self.queue.pop_front()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_sounds() {
+        assert_eq!(phonetic_key("Gonzales"), phonetic_key("GONZÁLEZ"));
+        assert_eq!(phonetic_key("Ximena"), phonetic_key("Jimena"));
+        assert_eq!(phonetic_key("Velázquez"), phonetic_key("Velasquez"));
+        assert_eq!(phonetic_key("Hernández"), phonetic_key("Ernandes"));
+        assert_eq!(phonetic_key("Castillo"), phonetic_key("Castiyo"));
+        assert_eq!(phonetic_key("Barrera"), phonetic_key("Varera"));
+    }
+
+    #[test]
+    fn codes() {
+        assert_eq!(phonetic_key("Velázquez"), "belaskes");
+        assert_eq!(
+            phonetic_key("Guillermo  Muñoz  Godoy"),
+            "giyermo munyos godoi"
+        );
+        assert_eq!(phonetic_key("Chávez Acción"), "cabes aksion");
+        assert_eq!(phonetic_key("Oaxaca Mexía Gerardo"), "oajaka mejia jerardo");
+        assert_eq!(phonetic_key("Axtla"), "akstla");
+    }
+}
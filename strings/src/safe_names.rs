@@ -0,0 +1,258 @@
+//! Names for worksheets and files that Excel and every operating system accept
+//!
+//! Excel rejects sheet names longer than 31 chars, with any of `[]:*?/\`, starting or ending with
+//! `'` or named `History`, and compares them ignoring case. Windows rejects file names with any of
+//! `<>:"/\|?*`, ending with a dot or a space, or named like a device (`CON`, `NUL`, `COM1`, etc.).
+//!
+//! ```
+//! use strings::safe_names::{file_name, sheet_name, UniqueNames};
+//!
+//! assert_eq!(sheet_name("Ingresos 2023/2024"), "Ingresos 2023_2024");
+//! assert_eq!(file_name("con.xlsx"), "con_.xlsx");
+//!
+//! let mut names = UniqueNames::sheets();
+//! assert_eq!(names.name("Profesores"), "Profesores");
+//! assert_eq!(names.name("PROFESORES"), "PROFESORES (2)");
+//! ```
+
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+};
+
+/// Maximum number of chars of a sheet name
+pub const MAX_SHEET_CHARS: usize = 31;
+/// Maximum number of bytes of a file name in most file systems
+pub const MAX_FILE_BYTES: usize = 255;
+/// Maximum number of bytes of an extension, after a longer one the dot is part of the stem
+const MAX_EXTENSION_BYTES: usize = 16;
+
+const SHEET_INVALID: &[char] = &['[', ']', ':', '*', '?', '/', '\\'];
+const FILE_INVALID: &[char] = &['<', '>', ':', '"', '/', '\\', '|', '?', '*'];
+const WINDOWS_RESERVED: &[&str] = &[
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8",
+    "COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+/// Sheet name that Excel accepts: invalid chars are replaced with `_`, it is truncated to
+/// [`MAX_SHEET_CHARS`] and an empty name becomes `"Hoja"`
+#[must_use]
+pub fn sheet_name(name: &str) -> String {
+    let name = replace_invalid(name, SHEET_INVALID);
+    let name = name.trim_matches(is_sheet_trimmed);
+
+    // The truncation may leave a `'` at the end again
+    let mut name = truncate_chars(name, MAX_SHEET_CHARS)
+        .trim_end_matches(is_sheet_trimmed)
+        .to_string();
+    if name.is_empty() {
+        name.push_str("Hoja");
+    } else if name.eq_ignore_ascii_case("History") {
+        name.push('_');
+    }
+
+    name
+}
+
+/// File name that every operating system accepts: invalid chars are replaced with `_`, device
+/// names before the first dot get a `_` after them and the stem is truncated so the name has at most
+/// [`MAX_FILE_BYTES`] bytes, keeping the extension. An empty name becomes `"archivo"`
+#[must_use]
+pub fn file_name(name: &str) -> String {
+    let name = replace_invalid(name, FILE_INVALID);
+    let name = name.trim().trim_end_matches('.');
+    let (stem, extension) = split_extension(name);
+
+    let mut stem = stem.trim_end().to_string();
+    // Windows also reserves the device names followed by any extension, like `con.tar.gz`
+    let device = stem.split('.').next().unwrap_or_default().trim_end();
+    if stem.is_empty() {
+        stem.push_str("archivo");
+    } else if WINDOWS_RESERVED
+        .iter()
+        .any(|reserved| device.eq_ignore_ascii_case(reserved))
+    {
+        stem.insert(device.len(), '_');
+    }
+
+    let stem = truncate_bytes(&stem, MAX_FILE_BYTES.saturating_sub(extension.len()));
+    format!("{}{extension}", stem.trim_end_matches(['.', ' ']))
+}
+
+/// `path` with its file name made safe with [`file_name`], the directories are kept as they are
+#[must_use]
+pub fn file_path(path: &Path) -> PathBuf {
+    match path.file_name() {
+        Some(name) => path.with_file_name(file_name(&name.to_string_lossy())),
+        None => path.to_path_buf(),
+    }
+}
+
+/// Generator of safe names that are unique ignoring case, repeated names get a suffix like
+/// `" (2)"` that fits in the maximum length
+#[derive(Debug, Clone)]
+pub struct UniqueNames {
+    sheets: bool,
+    used: HashSet<String>,
+}
+
+impl UniqueNames {
+    /// Unique sheet names for a workbook, see [`sheet_name`]
+    #[must_use]
+    pub fn sheets() -> Self {
+        Self {
+            sheets: true,
+            used: HashSet::new(),
+        }
+    }
+
+    /// Unique file names for a directory, see [`file_name`]
+    #[must_use]
+    pub fn files() -> Self {
+        Self {
+            sheets: false,
+            used: HashSet::new(),
+        }
+    }
+
+    /// Safe version of `name` that was not returned before
+    pub fn name(&mut self, name: &str) -> String {
+        let safe = if self.sheets {
+            sheet_name(name)
+        } else {
+            file_name(name)
+        };
+
+        let mut res = safe.clone();
+        let mut n = 2;
+        while !self.used.insert(res.to_lowercase()) {
+            res = self.with_suffix(&safe, &format!(" ({n})"));
+            n += 1;
+        }
+
+        res
+    }
+
+    fn with_suffix(&self, safe: &str, suffix: &str) -> String {
+        if self.sheets {
+            let base = truncate_chars(safe, MAX_SHEET_CHARS.saturating_sub(suffix.chars().count()))
+                .trim_end_matches(is_sheet_trimmed);
+            format!("{base}{suffix}")
+        } else {
+            let (stem, extension) = split_extension(safe);
+            let max = MAX_FILE_BYTES.saturating_sub(suffix.len() + extension.len());
+            let stem = truncate_bytes(stem, max);
+            format!("{stem}{suffix}{extension}")
+        }
+    }
+}
+
+/// Chars that can not start or end a sheet name, or that are not worth keeping there
+fn is_sheet_trimmed(c: char) -> bool {
+    c == '\'' || c.is_whitespace()
+}
+
+fn replace_invalid(name: &str, invalid: &[char]) -> String {
+    name.chars()
+        .map(|c| {
+            if invalid.contains(&c) || c.is_control() {
+                '_'
+            } else {
+                c
+            }
+        })
+        .collect()
+}
+
+/// Stem and extension with its dot, a leading dot or one followed by more than
+/// [`MAX_EXTENSION_BYTES`] is part of the stem
+fn split_extension(name: &str) -> (&str, &str) {
+    match name.rfind('.') {
+        Some(i) if i > 0 && name.len() - i <= MAX_EXTENSION_BYTES + 1 => name.split_at(i),
+        _ => (name, ""),
+    }
+}
+
+fn truncate_chars(text: &str, max: usize) -> &str {
+    text.char_indices()
+        .nth(max)
+        .map_or(text, |(end, _)| &text[..end])
+}
+
+fn truncate_bytes(text: &str, max: usize) -> &str {
+    let mut end = max.min(text.len());
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    &text[..end]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sheets() {
+        assert_eq!(sheet_name("'[Reporte]: ¿ok?'"), "_Reporte__ ¿ok_");
+        assert_eq!(sheet_name("history"), "history_");
+        assert_eq!(sheet_name("  "), "Hoja");
+        assert_eq!(
+            sheet_name("abcdefghijklmnopqrstuvwxyz1234'5678"),
+            "abcdefghijklmnopqrstuvwxyz1234"
+        );
+
+        let long = "Relación de profesores de asignatura por campus";
+        assert_eq!(sheet_name(long).chars().count(), MAX_SHEET_CHARS);
+
+        let mut names = UniqueNames::sheets();
+        assert_eq!(names.name(long), "Relación de profesores de asign");
+        assert_eq!(names.name(long), "Relación de profesores de a (2)");
+        assert_eq!(names.name(long), "Relación de profesores de a (3)");
+
+        let quoted = "Profesores de asignaturas 'MTY'";
+        assert_eq!(names.name(quoted), "Profesores de asignaturas 'MTY");
+        assert_eq!(names.name(quoted), "Profesores de asignaturas (2)");
+    }
+
+    #[test]
+    fn files() {
+        assert_eq!(
+            file_name("Profes: 2023/2024?.xlsx"),
+            "Profes_ 2023_2024_.xlsx"
+        );
+        assert_eq!(file_name("LPT1"), "LPT1_");
+        assert_eq!(file_name("con.tar.gz"), "con_.tar.gz");
+        assert_eq!(file_name("Aux .x.xlsx"), "Aux_ .x.xlsx");
+        assert_eq!(file_name("console.xlsx"), "console.xlsx");
+        assert_eq!(file_name("grupo. "), "grupo");
+        assert_eq!(file_name(".xlsx"), ".xlsx");
+
+        let long = format!("{}.xlsx", "ñ".repeat(200));
+        let name = file_name(&long);
+        assert!(name.len() <= MAX_FILE_BYTES && name.ends_with("ñ.xlsx"));
+
+        let mut names = UniqueNames::files();
+        assert_eq!(names.name("a.xlsx"), "a.xlsx");
+        assert_eq!(names.name("A.xlsx"), "A (2).xlsx");
+        let first = names.name(&long);
+        let second = names.name(&long);
+        assert!(second.len() <= MAX_FILE_BYTES && second.ends_with(" (2).xlsx"));
+        assert_ne!(first, second);
+
+        // A long text after the last dot is not an extension, so the name can still be shortened
+        let dotted = format!("Reporte. {}", "x".repeat(300));
+        let mut names = UniqueNames::files();
+        assert!(file_name(&dotted).len() <= MAX_FILE_BYTES);
+        assert!(names.name(&dotted).len() <= MAX_FILE_BYTES);
+        assert!(names.name(&dotted).ends_with(" (2)"));
+    }
+
+    #[test]
+    fn paths() {
+        assert_eq!(
+            file_path(Path::new("salida/CON.xlsx")),
+            Path::new("salida/CON_.xlsx")
+        );
+        assert_eq!(file_path(Path::new("/")), Path::new("/"));
+    }
+}
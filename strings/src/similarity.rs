@@ -0,0 +1,191 @@
+//! String similarity metrics normalized to `0..=1`, where 1 means equal
+//!
+//! The token based metrics split the text with [`get_words`], remove the punctuation around each
+//! word and normalize it with [`Normalizer`], so they ignore case, accents and word order:
+//!
+//! ```
+//! use strings::similarity::token_set_ratio;
+//!
+//! assert_eq!(token_set_ratio("Universidad Anáhuac México", "Anáhuac, Universidad"), 1.);
+//! ```
+
+use std::collections::BTreeSet;
+
+use crate::{edit_distance, get_words, Normalizer};
+
+/// Edit distance normalized by the length of the longest string
+#[must_use]
+pub fn edit_ratio(a: &str, b: &str) -> f64 {
+    let len = a.chars().count().max(b.chars().count());
+    if len == 0 {
+        return 1.;
+    }
+
+    1. - ratio(edit_distance(a, b), len)
+}
+
+/// Jaro similarity of the chars of `a` and `b`
+#[must_use]
+pub fn jaro(a: &str, b: &str) -> f64 {
+    let a = a.chars().collect::<Vec<_>>();
+    let b = b.chars().collect::<Vec<_>>();
+
+    if a.is_empty() && b.is_empty() {
+        return 1.;
+    }
+    if a.is_empty() || b.is_empty() {
+        return 0.;
+    }
+
+    let window = (a.len().max(b.len()) / 2).saturating_sub(1);
+    let mut b_matched = vec![false; b.len()];
+    let mut matched = Vec::with_capacity(a.len());
+
+    for (i, ca) in a.iter().enumerate() {
+        let start = i.saturating_sub(window);
+        let end = (i + window + 1).min(b.len());
+
+        if let Some(j) = (start..end).find(|&j| !b_matched[j] && b[j] == *ca) {
+            b_matched[j] = true;
+            matched.push(*ca);
+        }
+    }
+
+    if matched.is_empty() {
+        return 0.;
+    }
+
+    let matched_b = b
+        .iter()
+        .zip(&b_matched)
+        .filter(|(_, m)| **m)
+        .map(|(c, _)| c);
+    let transpositions = matched
+        .iter()
+        .zip(matched_b)
+        .filter(|(a, b)| a != b)
+        .count();
+    let m = matched.len();
+
+    (ratio(m, a.len()) + ratio(m, b.len()) + ratio(m - transpositions / 2, m)) / 3.
+}
+
+/// Jaro-Winkler similarity, a [`jaro`] similarity that favors strings with a common prefix of up to
+/// four chars
+#[must_use]
+pub fn jaro_winkler(a: &str, b: &str) -> f64 {
+    const BOOST_THRESHOLD: f64 = 0.7;
+    const PREFIX_SCALE: f64 = 0.1;
+
+    let sim = jaro(a, b);
+    if sim <= BOOST_THRESHOLD {
+        return sim;
+    }
+
+    let prefix = a
+        .chars()
+        .zip(b.chars())
+        .take(4)
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    sim + ratio(prefix, 1) * PREFIX_SCALE * (1. - sim)
+}
+
+/// [`edit_ratio`] of the tokens of `a` and `b` after sorting them
+#[must_use]
+pub fn token_sort_ratio(a: &str, b: &str) -> f64 {
+    let mut a = tokens(a);
+    let mut b = tokens(b);
+    a.sort_unstable();
+    b.sort_unstable();
+
+    edit_ratio(&a.join(" "), &b.join(" "))
+}
+
+/// Like [`token_sort_ratio`] but comparing the common tokens against each side, so a name that is
+/// contained in the other one gets a similarity of 1
+#[must_use]
+pub fn token_set_ratio(a: &str, b: &str) -> f64 {
+    let a = tokens(a).into_iter().collect::<BTreeSet<_>>();
+    let b = tokens(b).into_iter().collect::<BTreeSet<_>>();
+
+    let join = |tokens: Vec<&String>| tokens.into_iter().cloned().collect::<Vec<_>>().join(" ");
+    let common = join(a.intersection(&b).collect());
+    let only_a = join(a.difference(&b).collect());
+    let only_b = join(b.difference(&a).collect());
+
+    let with_a = format!("{common} {only_a}").trim().to_string();
+    let with_b = format!("{common} {only_b}").trim().to_string();
+
+    let mut best = edit_ratio(&with_a, &with_b);
+    if !common.is_empty() {
+        best = best
+            .max(edit_ratio(&common, &with_a))
+            .max(edit_ratio(&common, &with_b));
+    }
+
+    best
+}
+
+/// Size of the intersection over the size of the union of the sets of tokens of `a` and `b`
+#[must_use]
+pub fn jaccard(a: &str, b: &str) -> f64 {
+    let a = tokens(a).into_iter().collect::<BTreeSet<_>>();
+    let b = tokens(b).into_iter().collect::<BTreeSet<_>>();
+
+    let union = a.union(&b).count();
+    if union == 0 {
+        return 1.;
+    }
+
+    ratio(a.intersection(&b).count(), union)
+}
+
+/// Normalized words of `text` without the punctuation around them
+fn tokens(text: &str) -> Vec<String> {
+    get_words(text)
+        .map(|w| Normalizer::new().normalize(w.trim_matches(|c: char| !c.is_alphanumeric())))
+        .filter(|w| !w.is_empty())
+        .collect()
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn ratio(num: usize, den: usize) -> f64 {
+    num as f64 / den as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-3
+    }
+
+    #[test]
+    fn jaro_winkler_known_values() {
+        assert!(close(jaro("MARTHA", "MARHTA"), 0.944));
+        assert!(close(jaro_winkler("MARTHA", "MARHTA"), 0.961));
+        assert!(close(jaro_winkler("DIXON", "DICKSONX"), 0.813));
+        assert!(close(jaro_winkler("", ""), 1.));
+        assert!(close(jaro_winkler("abc", ""), 0.));
+    }
+
+    #[test]
+    fn token_metrics() {
+        let a = "Universidad Anáhuac México";
+        let b = "Anáhuac, Universidad";
+
+        assert!(close(token_sort_ratio("Pérez Juan", "juan perez"), 1.));
+        assert!(close(token_set_ratio(a, b), 1.));
+        assert!(close(jaccard(a, b), 2. / 3.));
+        assert!(token_sort_ratio(a, b) < 1.);
+    }
+
+    #[test]
+    fn edit() {
+        assert!(close(edit_ratio("gonzales", "gonzalez"), 0.875));
+        assert!(close(edit_ratio("", ""), 1.));
+    }
+}
@@ -47,9 +47,10 @@
 // Campus
 // Orden
 
+use ::strings::numbers::NumberFormat;
 use polars::{io::SerReader, lazy::frame::IntoLazy};
 use polars::{lazy::dsl::*, prelude::*};
-use polars_excel_writer::PolarsXlsxWriter;
+use polars_sheet_reader::{write_xlsx, DipeExpr};
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     let df = polars::prelude::CsvReader::from_path(concat!(
@@ -230,6 +231,8 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     let df_final =
         concat([df_2021, df_2022, df_2023].as_slice(), UnionArgs::default())?.collect()?;
 
+    // Los valores llegan como texto, como "1,234" o "85%", que un `cast` convierte en nulos
+    let numeros = NumberFormat::new();
     let df_final = df_final
         .lazy()
         .select([
@@ -237,34 +240,52 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             col("Año"),
             col("Área de Estudio"),
             col("Posición ranking reforma").cast(DataType::String),
-            col("Cuota mercado").cast(DataType::Float64),
-            col("Número estudiantes").cast(DataType::UInt64),
-            col("CAGR matrícula").cast(DataType::Float64),
-            col("# profesores investigadores").cast(DataType::UInt64),
-            col("Número de publicaciones").cast(DataType::UInt64),
-            col("NPS").cast(DataType::Float64),
-            col("Lugar NPS relativo").cast(DataType::UInt64),
-            col("Número de aplicaciones").cast(DataType::UInt64),
-            col("Aceptados/Aplicaciones").cast(DataType::Float64),
-            col("Tasa matriculación (Inscritos/aceptados)").cast(DataType::Float64),
-            col("Calificación").cast(DataType::UInt64),
-            col("Sustenantes/total egresados").cast(DataType::Float64),
-            col("Satisfactorio/Sustentantes").cast(DataType::Float64),
-            col("Relative Market Share").cast(DataType::Float64),
-            col("Orden").cast(DataType::UInt64),
+            col("Cuota mercado").dipe().parse_number(numeros),
+            col("Número estudiantes")
+                .dipe()
+                .parse_number(numeros)
+                .cast(DataType::UInt64),
+            col("CAGR matrícula").dipe().parse_number(numeros),
+            col("# profesores investigadores")
+                .dipe()
+                .parse_number(numeros)
+                .cast(DataType::UInt64),
+            col("Número de publicaciones")
+                .dipe()
+                .parse_number(numeros)
+                .cast(DataType::UInt64),
+            col("NPS").dipe().parse_number(numeros),
+            col("Lugar NPS relativo")
+                .dipe()
+                .parse_number(numeros)
+                .cast(DataType::UInt64),
+            col("Número de aplicaciones")
+                .dipe()
+                .parse_number(numeros)
+                .cast(DataType::UInt64),
+            col("Aceptados/Aplicaciones").dipe().parse_number(numeros),
+            col("Tasa matriculación (Inscritos/aceptados)")
+                .dipe()
+                .parse_number(numeros),
+            col("Calificación")
+                .dipe()
+                .parse_number(numeros)
+                .cast(DataType::UInt64),
+            col("Sustenantes/total egresados")
+                .dipe()
+                .parse_number(numeros),
+            col("Satisfactorio/Sustentantes")
+                .dipe()
+                .parse_number(numeros),
+            col("Relative Market Share").dipe().parse_number(numeros),
+            col("Orden")
+                .dipe()
+                .parse_number(numeros)
+                .cast(DataType::UInt64),
         ])
         .collect()?;
 
-    write_xlsx(&df_final, "procesado.xlsx")?;
-
-    Ok(())
-}
-
-fn write_xlsx(df: &DataFrame, name: &str) -> Result<(), Box<dyn std::error::Error>> {
-    let mut writer = PolarsXlsxWriter::new();
-    writer.set_autofit(true);
-    writer.write_dataframe(df)?;
-    writer.save(name)?;
+    write_xlsx(&df_final, "procesado.xlsx", true)?;
 
     Ok(())
 }
@@ -9,8 +9,12 @@ use bstr::{BStr, BString, ByteSlice};
 use calamine::{open_workbook_auto, DataType, Reader};
 use calamine::{Data, Sheets};
 use clap::Parser;
+use dialogs::{
+    ask_args, ask_cancel, ask_paths, ask_yes_no, clear_history, install_panic_hook, msg,
+    report_error, set_lang, set_log_dir, show_message, wants_form, with_progress, Lang, Level,
+    PathKind, PathRequest, ProgressMode, Task,
+};
 use itertools::Itertools;
-use rfd::{FileDialog, MessageButtons, MessageDialog, MessageDialogResult, MessageLevel};
 use rust_xlsxwriter::{Color, ExcelDateTime, Workbook, Worksheet};
 use std::fs::File;
 use std::io::BufReader;
@@ -24,8 +28,10 @@ use std::{
     process::ExitCode,
     sync::atomic::{AtomicUsize, Ordering},
 };
-use strings::get_words;
-use thiserror::Error;
+use strings::{
+    get_words,
+    safe_names::{file_name, UniqueNames},
+};
 use walkdir::WalkDir;
 
 static NUMERO_CAMPUS: AtomicUsize = AtomicUsize::new(0);
@@ -33,28 +39,105 @@ static FROM_CLI: AtomicBool = AtomicBool::new(false);
 
 #[derive(Parser)]
 struct Args {
+    /// Archivos o carpetas a concentrar
     rutas: Vec<PathBuf>,
-    // Directorio donde poner los concentrados
+    /// Directorio donde poner los concentrados
     #[clap(long, short)]
     salida: Option<PathBuf>,
-    // Número de campus de la escuela
+    /// Número de campus de la escuela
     #[clap(long, short, default_value = "3")]
     numero_campus: usize,
-    // Escribir descartados
+    /// Escribir descartados
     #[clap(long, short)]
     descartados: bool,
-    // No mostrar dialogos nativos
+    /// No mostrar dialogos nativos
     #[clap(long, short)]
     cli: bool,
-    // Reset destination directory
+    /// Vaciar el directorio de salida
     #[clap(long, short)]
     reset: bool,
+    /// Olvidar las carpetas y archivos elegidos antes en los diálogos
+    #[clap(long)]
+    olvidar: bool,
+    /// Idioma de los mensajes, `es` o `en`. Por defecto el de `DIALOGS_LANG` o el del sistema, y
+    /// español si no hay ninguno
+    #[clap(long)]
+    idioma: Option<Lang>,
 }
 
-#[derive(Error, Debug)]
-enum MiError {
-    #[error("Error de IO: ")]
-    IoError(#[from] std::io::Error),
+/// Mensajes en español e inglés
+mod textos {
+    use dialogs::Text;
+
+    pub const REEMPLAZAR: Text = Text::new("¿Reemplazar directorio?", "Replace folder?");
+    pub const REEMPLAZAR_DESCRIPCION: Text = Text::new(
+        "¿El directorio no está vacío, deseas eliminar su contenido? Si eliges que no, solo se sobreescribirán los archivos existentes.",
+        "The folder isn't empty, do you want to delete its contents? If you choose no, only the existing files will be overwritten.",
+    );
+    pub const DESCARTADOS: Text = Text::new("Descartados", "Discarded");
+    pub const DESCARTADOS_DESCRIPCION: Text = Text::new(
+        "¿Deseas escribir las filas descartadas?",
+        "Do you want to write the discarded rows?",
+    );
+    pub const CONCENTRANDO: Text = Text::new("Concentrando archivos", "Merging files");
+    pub const CANCELADO: Text = Text::new(
+        "Se canceló antes de terminar, los grupos que no habían terminado no se escribieron",
+        "It was cancelled before finishing, the groups that hadn't finished weren't written",
+    );
+    pub const LISTO: Text = Text::new("Listo!!", "Done!!");
+    pub const SELECCION_MANUAL: Text = Text::new("Selección manual", "Manual selection");
+    pub const SELECCION_MANUAL_DESCRIPCION: Text = Text::new(
+        "¿Deseas selccionar los archivos a concentrar manualmente? Si eliges que no solo seleccionarás el directorio que contiene todos los archivos",
+        "Do you want to select the files to merge manually? If you choose no you will only select the folder that contains all the files",
+    );
+    pub const SELECCIONA_ARCHIVOS: Text = Text::new("Selecciona los archivos", "Select the files");
+    pub const SELECCIONA_CARPETA: Text = Text::new(
+        "Selecciona la carpeta con los archivos",
+        "Select the folder with the files",
+    );
+    pub const SALIDA: Text = Text::new(
+        "¿Dónde almacenar los generados?",
+        "Where to store the generated files?",
+    );
+    pub const VOLVER_A_INTENTAR: Text = Text::new("¿Volver a intentar?", "Try again?");
+    pub const VOLVER_A_INTENTAR_DESCRIPCION: Text = Text::new(
+        "No elegiste ningún archivo. ¿Quieres volver a intentar?",
+        "You didn't choose any file. Do you want to try again?",
+    );
+    pub const SALIDA_NO_DIRECTORIO: Text = Text::new(
+        "La ruta de salida no es un directorio",
+        "The output path isn't a folder",
+    );
+    pub const SIN_RUTAS: Text = Text::new(
+        "No se especificaron archivos o carpetas a concentrar",
+        "No files or folders to merge were given",
+    );
+    pub const NO_VACIAR_ACTUAL: Text = Text::new(
+        "No se vacía el directorio actual porque no se especificó como salida",
+        "The current folder isn't emptied because it wasn't given as output",
+    );
+    pub const DESCARTANDO_HOJA: Text = Text::new(
+        "Descartando hoja {} porque es de desplegables",
+        "Discarding sheet {} because it has dropdown lists",
+    );
+    pub const ESCRIBIENDO: Text = Text::new("Escribiendo {}", "Writing {}");
+    pub const NO_SE_PUDO_ABRIR: Text = Text::new("No se pudo abrir {}", "{} couldn't be opened");
+    pub const NO_SE_PUDO_GUARDAR: Text = Text::new("No se pudo guardar {}", "{} couldn't be saved");
+    pub const FECHA_INVALIDA: Text =
+        Text::new("Fecha invalida en fila {}", "Invalid date in row {}");
+    pub const HOJA_SIN_ENCABEZADO: Text =
+        Text::new("Hoja sin encabezado en {}", "Sheet without header in {}");
+    pub const DEMASIADAS_COLUMNAS: Text =
+        Text::new("Demasiadas columnas en {}", "Too many columns in {}");
+    pub const ERROR_RUTA: Text = Text::new(
+        "Hubo un error con la ruta especificada {}",
+        "There was an error with the path {}",
+    );
+    #[cfg(target_os = "windows")]
+    pub const RUTA_WINDOWS: Text = Text::new(
+        "Si la ruta la especificaste como `.\\directorio\\` intenta especificarlo como `.\\directorio`, eliminando la última diagonal inversa \\",
+        "If you wrote the path as `.\\folder\\` try writing it as `.\\folder`, removing the last backslash \\",
+    );
 }
 
 #[derive(Debug)]
@@ -64,6 +147,17 @@ struct PreGrupo {
 }
 
 fn main() -> ExitCode {
+    // Sin argumentos en una terminal se piden todos en un formulario en lugar de un diálogo por
+    // parámetro. Si en el formulario no se dan rutas, se piden con diálogos como sin formulario
+    let (args, formulario) = if wants_form() {
+        let Some(args) = ask_args::<Args>(&["cli", "olvidar"]) else {
+            return ExitCode::SUCCESS;
+        };
+        (args, true)
+    } else {
+        (Args::parse(), false)
+    };
+
     let Args {
         mut rutas,
         numero_campus,
@@ -71,32 +165,54 @@ fn main() -> ExitCode {
         descartados: mut descatrados,
         cli,
         mut reset,
-    } = Args::parse();
+        olvidar,
+        idioma,
+    } = args;
 
-    FROM_CLI.store(
-        cli || (rutas.is_empty().not() || descatrados || salida.is_some()),
-        Ordering::Relaxed,
-    );
+    if let Some(idioma) = idioma {
+        set_lang(idioma);
+    }
+
+    if olvidar {
+        if let Err(err) = clear_history() {
+            eprintln!("{}", msg::HISTORY_NOT_CLEARED.fill(err));
+        }
+    }
+
+    let from_cli = if formulario {
+        rutas.is_empty().not()
+    } else {
+        cli || (rutas.is_empty().not() || descatrados || salida.is_some())
+    };
+    FROM_CLI.store(from_cli, Ordering::Relaxed);
     NUMERO_CAMPUS.store(numero_campus, Ordering::Relaxed);
 
+    // Sin consola los pánicos se pierden, así que se muestran en una ventana
+    if !FROM_CLI.load(Ordering::Relaxed) {
+        install_panic_hook();
+    }
+
+    if rutas.is_empty() && from_cli {
+        eprintln!("{}: {}", msg::ERROR, textos::SIN_RUTAS);
+        return ExitCode::FAILURE;
+    }
+
+    // Sin salida en la línea de comandos se usa el directorio actual, que nunca se vacía
+    let salida_implicita = salida.is_none() && from_cli;
     let salida = match get_directorio_salida(salida) {
         Ok(value) => value,
         Err(value) => return value,
     };
+    set_log_dir(&salida);
 
     if salida.read_dir().is_ok_and(|dir| dir.count() != 0) {
         if !FROM_CLI.load(Ordering::Relaxed) {
-            let res = MessageDialog::new()
-                .set_title("¿Reemplazar directorio?")
-                .set_description("¿El directorio no está vacío, deseas eliminar su contenido? Si eliges que no, solo se sobreescribirán los archivos existentes.")
-                .set_level(MessageLevel::Warning)
-                .set_buttons(MessageButtons::YesNo)
-                .show();
-
-            reset = matches!(res, MessageDialogResult::Yes);
+            reset = ask_yes_no(textos::REEMPLAZAR, textos::REEMPLAZAR_DESCRIPCION.get());
         }
 
-        if reset {
+        if reset && salida_implicita {
+            eprintln!("{}", textos::NO_VACIAR_ACTUAL);
+        } else if reset {
             std::fs::remove_dir_all(&salida).unwrap();
             std::fs::create_dir_all(&salida).unwrap();
         }
@@ -109,26 +225,20 @@ fn main() -> ExitCode {
     }
 
     if !FROM_CLI.load(Ordering::Relaxed) {
-        let res = MessageDialog::new()
-            .set_title("Descartados")
-            .set_description("¿Deseas escribir las filas descartadas?")
-            .set_level(MessageLevel::Info)
-            .set_buttons(MessageButtons::YesNo)
-            .show();
-
-        descatrados = matches!(res, MessageDialogResult::Yes);
+        descatrados = ask_yes_no(textos::DESCARTADOS, textos::DESCARTADOS_DESCRIPCION.get());
     };
 
     let grupos = obtener_grupos_por_nombre(&rutas, &["xlsx", "xlsm", "xlsb", "xls"]);
-    let mut grupos = grupos
+    let grupos = grupos
         .into_iter()
         .map(|(k, v)| {
-            (k, {
-                let headers = headers_from_file(&v[0]);
-                PreGrupo { rutas: v, headers }
-            })
+            let headers = headers_from_file(&v[0])?;
+            Some((k, PreGrupo { rutas: v, headers }))
         })
-        .collect::<HashMap<_, _>>();
+        .collect::<Option<HashMap<_, _>>>();
+    let Some(mut grupos) = grupos else {
+        return ExitCode::FAILURE;
+    };
 
     // Agrupamos los grupos que tienen los mismos encabezados
     'a: loop {
@@ -155,7 +265,7 @@ fn main() -> ExitCode {
                     let mut val1 = grupos.remove(key1).unwrap();
                     let val2 = grupos.remove(key2).unwrap();
 
-                    val1.rutas.extend(val2.rutas.into_iter());
+                    val1.rutas.extend(val2.rutas);
                     grupos.insert(final_key, val1);
 
                     continue 'a;
@@ -176,55 +286,90 @@ fn main() -> ExitCode {
     // Descartamos los grupos que no tienen más de un archivo
     grupos.retain(|_, v| v.len() > 1);
 
-    std::thread::scope(|s| {
-        for (grupo, rutas) in &mut grupos {
-            s.spawn(|| {
-                let mut rutas = rutas
-                    .iter_mut()
-                    .map(|ruta| {
-                        let ruta = std::mem::take(ruta);
-                        let excel = open_workbook_auto(&ruta).unwrap();
-                        (ruta, excel)
-                    })
-                    .collect_vec();
+    let modo = if FROM_CLI.load(Ordering::Relaxed) {
+        ProgressMode::Terminal
+    } else {
+        ProgressMode::auto()
+    };
 
-                let headers = extract_headers(&mut rutas);
-                let mut name_buff = Vec::new();
+    let fallidos = AtomicUsize::new(0);
+    let cancelado = with_progress(modo, textos::CONCENTRANDO.get(), |progress| {
+        let salida = &salida;
+        let fallidos = &fallidos;
 
-                name_buff.clear();
-                name_buff.extend_from_slice(grupo.as_bstr());
-                name_buff.extend_from_slice(b".xlsx".as_slice());
+        std::thread::scope(|s| {
+            for (grupo, rutas) in &mut grupos {
+                let grupo = grupo.to_str_lossy();
+                // Un paso por archivo leído, después se suma uno por hoja y uno por guardar
+                let task = progress.task(grupo.as_ref(), rutas.len() as u64);
 
-                let xlsx_salida = concat_path(&salida, unsafe {
-                    OsStr::from_encoded_bytes_unchecked(&name_buff)
-                });
+                s.spawn(move || {
+                    let mut abiertos = Vec::with_capacity(rutas.len());
+                    for ruta in rutas.iter_mut() {
+                        if task.is_cancelled() {
+                            return;
+                        }
 
-                name_buff.clear();
-                name_buff.extend_from_slice(grupo.as_bstr());
-                name_buff.extend_from_slice(b".descartados.xlsx".as_slice());
+                        let ruta = std::mem::take(ruta);
+                        task.set_message(ruta.file_name().unwrap_or_default().to_string_lossy());
+                        let Some(excel) = abrir(&ruta) else {
+                            fallidos.fetch_add(1, Ordering::Relaxed);
+                            return;
+                        };
+                        abiertos.push((ruta, excel));
+                        task.inc(1);
+                    }
+
+                    let mut rutas = abiertos;
+                    let headers = extract_headers(&mut rutas, &task);
+                    task.set_total((rutas.len() + headers.hojas.len() + 1) as u64);
 
-                let xlsx_descartados = concat_path(&salida, unsafe {
-                    OsStr::from_encoded_bytes_unchecked(&name_buff)
+                    let nombre = file_name(&format!("{grupo}.xlsx"));
+                    let xlsx_salida = concat_path(salida, nombre.as_ref());
+
+                    let nombre = file_name(&format!("{grupo}.descartados.xlsx"));
+                    let xlsx_descartados = concat_path(salida, nombre.as_ref());
+
+                    let guardado = write_to_file(
+                        &mut rutas,
+                        &headers,
+                        &xlsx_salida,
+                        descatrados.then_some(&xlsx_descartados),
+                        &task,
+                    );
+                    if !guardado {
+                        fallidos.fetch_add(1, Ordering::Relaxed);
+                    }
                 });
+            }
+        });
 
-                write_to_file(
-                    &mut rutas,
-                    &headers,
-                    &xlsx_salida,
-                    descatrados.then_some(&xlsx_descartados),
-                );
-            });
-        }
+        progress.is_cancelled()
     });
 
+    if cancelado {
+        if FROM_CLI.load(Ordering::Relaxed) {
+            println!("{}", textos::CANCELADO);
+        } else {
+            show_message(
+                Level::Warning,
+                msg::CANCELLED.get(),
+                textos::CANCELADO.get(),
+            );
+        }
+
+        return ExitCode::FAILURE;
+    }
+
+    // Los errores ya se le mostraron al usuario
+    if fallidos.load(Ordering::Relaxed) > 0 {
+        return ExitCode::FAILURE;
+    }
+
     if FROM_CLI.load(Ordering::Relaxed) {
-        println!("Listo!!");
+        println!("{}", textos::LISTO);
     } else {
-        let _ = MessageDialog::new()
-            .set_title("Listo")
-            .set_description("Listo!!")
-            .set_level(MessageLevel::Info)
-            .show();
+        show_message(Level::Info, msg::DONE.get(), textos::LISTO.get());
     }
 
     ExitCode::SUCCESS
@@ -232,37 +377,22 @@ fn main() -> ExitCode {
 
 fn dialogo_pedir_rutas_entrada(rutas: &mut Vec<PathBuf>) -> Option<ExitCode> {
     loop {
-        let multiples = MessageDialog::new()
-            .set_title("Selección manual")
-            .set_description("¿Deseas selccionar los archivos a concentrar manualmente? Si eliges que no solo seleccionarás el directorio que contiene todos los archivos")
-            .set_level(MessageLevel::Info)
-            .set_buttons(MessageButtons::YesNo)
-            .show();
-
-        let multiples = matches!(multiples, MessageDialogResult::Yes);
-
-        let vec = if multiples {
-            FileDialog::new()
-                .set_title("Selecciona los archivos")
-                .add_filter("Excel", &["xlsx"])
-                .pick_files()
+        let multiples = ask_yes_no(
+            textos::SELECCION_MANUAL,
+            textos::SELECCION_MANUAL_DESCRIPCION.get(),
+        );
+
+        let request = if multiples {
+            PathRequest::new(
+                PathKind::OpenFiles,
+                textos::SELECCIONA_ARCHIVOS,
+                Some(&[("Excel", &["xlsx"])]),
+            )
         } else {
-            FileDialog::new()
-                .set_title("Selecciona la carpeta con los archivos")
-                .pick_folder()
-                .map(|a| vec![a])
+            PathRequest::new(PathKind::OpenFolder, textos::SELECCIONA_CARPETA, None)
         };
-        let Some(res) = vec else {
-            let cancelar = MessageDialog::new()
-                .set_title("Cancelar")
-                .set_description("¿Deseas cancelar la acción y salir del programa?")
-                .set_level(MessageLevel::Warning)
-                .set_buttons(MessageButtons::YesNo)
-                .show();
-
-            let cancelar = matches!(cancelar, MessageDialogResult::Yes);
-
-            if cancelar {
+        let Some(res) = ask_paths(&request, |_| Ok(())) else {
+            if ask_cancel() {
                 return Some(ExitCode::SUCCESS);
             }
             continue;
@@ -297,12 +427,14 @@ fn vec_differences<'a, T: PartialEq>(mut a: &'a [T], mut b: &'a [T]) -> usize {
     b.iter().filter(|b| a.contains(b).not()).count()
 }
 
+/// Regresa `false` si no se pudo guardar alguno de los archivos
 fn write_to_file(
     rutas: &mut [(PathBuf, Sheets<BufReader<File>>)],
     headers: &Headers,
     salida: &Path,
     descartados: Option<&Path>,
-) {
+    task: &Task,
+) -> bool {
     let mut workbook = Workbook::new();
     let mut wb_descartados = Workbook::new();
     let mut sheet_descartados = Worksheet::new();
@@ -318,25 +450,30 @@ fn write_to_file(
 
     let sheet_descartados = wb_descartados.worksheet_from_name("Descartados").unwrap();
     let mut c_row_descartados = 0;
+    let mut nombres_hojas = UniqueNames::sheets();
 
     for hoja in &headers.hojas {
+        if task.is_cancelled() {
+            return true;
+        }
+        task.set_message(hoja);
+        task.inc(1);
+
         if get_words(&hoja.to_lowercase())
             .next()
             .is_some_and(|a| a.starts_with("desp"))
         {
-            if FROM_CLI.load(Ordering::Relaxed) {
-                println!("Descartando hoja {hoja:?} porque es de desplegables");
-            }
+            task.println(textos::DESCARTANDO_HOJA.fill(format!("{hoja:?}")));
             continue;
         }
 
-        if workbook.worksheet_from_name(hoja).is_err() {
-            let mut worksheet = Worksheet::new();
-            worksheet.set_name(hoja).unwrap();
-            workbook.push_worksheet(worksheet);
-        }
+        // Excel no acepta cualquier nombre de hoja
+        let nombre_hoja = nombres_hojas.name(hoja);
+        let mut worksheet = Worksheet::new();
+        worksheet.set_name(&nombre_hoja).unwrap();
+        workbook.push_worksheet(worksheet);
 
-        let worksheet = workbook.worksheet_from_name(hoja).unwrap();
+        let worksheet = workbook.worksheet_from_name(&nombre_hoja).unwrap();
 
         let encabezados = headers.encabezados.get(hoja.trim()).unwrap();
         let encabezados_unicos = headers.encabezados_unicos.get(hoja.trim()).unwrap();
@@ -404,6 +541,7 @@ fn write_to_file(
                             c_row_descartados,
                             col.try_into().unwrap(),
                             &format,
+                            task,
                         );
                     }
                 }
@@ -426,7 +564,14 @@ fn write_to_file(
                     let n_col = final_col.try_into().expect("Esto no debería pasar nunca");
                     let format = rust_xlsxwriter::Format::new();
 
-                    write_cell(cell, worksheet, n_row.try_into().unwrap(), n_col, &format);
+                    write_cell(
+                        cell,
+                        worksheet,
+                        n_row.try_into().unwrap(),
+                        n_col,
+                        &format,
+                        task,
+                    );
 
                     if n_row > added {
                         added = i;
@@ -455,21 +600,44 @@ fn write_to_file(
             });
     }
 
-    if FROM_CLI.load(Ordering::Relaxed) {
-        println!("Escribiendo {}", salida.display());
-    }
-
-    workbook.save(salida).unwrap();
+    task.set_message(textos::ESCRIBIENDO.fill(salida.display()));
+    let mut guardado = guardar(&mut workbook, salida);
     if let Some(descartados) = descartados {
-        if FROM_CLI.load(Ordering::Relaxed) {
-            println!("Escribiendo {}", descartados.display());
-        }
-        wb_descartados.save(descartados).unwrap();
+        task.set_message(textos::ESCRIBIENDO.fill(descartados.display()));
+        guardado &= guardar(&mut wb_descartados, descartados);
     }
+    task.inc(1);
+
+    guardado
+}
+
+/// Abre `ruta`, si no se puede le dice al usuario por qué
+fn abrir(ruta: &Path) -> Option<Sheets<BufReader<File>>> {
+    open_workbook_auto(ruta)
+        .map_err(|err| {
+            report_error(
+                &textos::NO_SE_PUDO_ABRIR.fill(ruta.display()),
+                &err.to_string(),
+            );
+        })
+        .ok()
+}
+
+/// Guarda `workbook` en `ruta`, si no se puede le dice al usuario por qué
+fn guardar(workbook: &mut Workbook, ruta: &Path) -> bool {
+    workbook
+        .save(ruta)
+        .map_err(|err| {
+            report_error(
+                &textos::NO_SE_PUDO_GUARDAR.fill(ruta.display()),
+                &err.to_string(),
+            );
+        })
+        .is_ok()
 }
 
-fn headers_from_file(ruta: &Path) -> Vec<Vec<String>> {
-    let mut excel = open_workbook_auto(ruta).unwrap();
+fn headers_from_file(ruta: &Path) -> Option<Vec<Vec<String>>> {
+    let mut excel = abrir(ruta)?;
     let sheets = excel.sheet_names();
     let mut res = Vec::with_capacity(sheets.len());
 
@@ -529,7 +697,7 @@ fn headers_from_file(ruta: &Path) -> Vec<Vec<String>> {
         res.push(headers);
     }
 
-    res
+    Some(res)
 }
 
 fn vacio(data: &Data, col_info: &ColumnInfo) -> bool {
@@ -552,6 +720,7 @@ fn write_cell<'a>(
     n_row: u32,
     n_col: u16,
     format: &'a rust_xlsxwriter::Format,
+    task: &Task,
 ) -> Option<Result<&'a mut Worksheet, rust_xlsxwriter::XlsxError>> {
     Some(match cell {
         Data::Int(val) => worksheet.write_with_format(n_row, n_col, *val, format),
@@ -569,21 +738,21 @@ fn write_cell<'a>(
                 if let Ok(d) = ExcelDateTime::from_serial_datetime(val.as_f64()) {
                     d
                 } else {
-                    println!("Fecha invalida en fila {val}");
+                    task.println(textos::FECHA_INVALIDA.fill(val));
                     ExcelDateTime::from_ymd(1900, 1, 1).unwrap()
                 },
                 &format,
             )
         }
         Data::Error(val) => {
-            println!("Error: {val:?}");
+            task.println(format!("{}: {val:?}", msg::ERROR));
             worksheet.write_with_format(n_row, n_col, "", format)
         }
         Data::Empty => return None,
     })
 }
 
-fn extract_headers(rutas: &mut Vec<(PathBuf, Sheets<BufReader<File>>)>) -> Headers {
+fn extract_headers(rutas: &mut Vec<(PathBuf, Sheets<BufReader<File>>)>, task: &Task) -> Headers {
     let mut encabezados = Headers {
         encabezados: HashMap::new(),
         encabezados_unicos: HashMap::new(),
@@ -616,7 +785,12 @@ fn extract_headers(rutas: &mut Vec<(PathBuf, Sheets<BufReader<File>>)>) -> Heade
                 .rows()
                 .filter(|row| row.first().is_some_and(|cell| cell.is_empty().not()));
             let Some(encabezados) = rows.next() else {
-                println!("Error: Hoja sin encabezado en {}:{sheet}", ruta.display());
+                let hoja = format!("{}:{sheet}", ruta.display());
+                task.println(format!(
+                    "{}: {}",
+                    msg::ERROR,
+                    textos::HOJA_SIN_ENCABEZADO.fill(hoja)
+                ));
                 continue;
             };
 
@@ -643,12 +817,12 @@ fn extract_headers(rutas: &mut Vec<(PathBuf, Sheets<BufReader<File>>)>) -> Heade
                 let encabezados = entrada.entry(ruta.clone()).or_default();
 
                 let Ok(col): Result<u16, _> = col.try_into() else {
-                    println!(
-                        "Error: Demasiadas columnas en {}:{}:{}",
-                        ruta.display(),
-                        sheet,
-                        col
-                    );
+                    let columna = format!("{}:{}:{}", ruta.display(), sheet, col);
+                    task.println(format!(
+                        "{}: {}",
+                        msg::ERROR,
+                        textos::DEMASIADAS_COLUMNAS.fill(columna)
+                    ));
                     continue;
                 };
 
@@ -749,9 +923,11 @@ fn log_grupos(salida: &Path, grupos: &HashMap<BString, Vec<PathBuf>>) {
 
     grupos_excel.push_worksheet(worksheet);
 
-    grupos_excel
-        .save(concat_path(salida, "grupos.xlsx".as_ref()))
-        .unwrap();
+    // Solo es un registro de los grupos, si no se guarda se puede concentrar de todos modos
+    guardar(
+        &mut grupos_excel,
+        &concat_path(salida, "grupos.xlsx".as_ref()),
+    );
 }
 
 fn get_directorio_salida(salida: Option<PathBuf>) -> Result<PathBuf, ExitCode> {
@@ -761,19 +937,18 @@ fn get_directorio_salida(salida: Option<PathBuf>) -> Result<PathBuf, ExitCode> {
         let salida = if let Some(salida) = salida {
             salida
         } else if !from_cli {
-            let salida = FileDialog::new()
-                .set_title("¿Dónde almacenar los generados?")
-                .set_directory(&current_dir().unwrap())
-                .pick_folder();
+            let directorio = current_dir().unwrap();
+            let request = PathRequest {
+                directory: Some(&directorio),
+                ..PathRequest::new(PathKind::OpenFolder, textos::SALIDA, None)
+            };
+            let salida = ask_paths(&request, |_| Ok(())).and_then(|a| a.into_iter().next());
 
             let Some(salida) = salida else {
-                let continuar = MessageDialog::new()
-                    .set_title("¿Volver a intentar?")
-                    .set_description("No elegiste ningún archivo. ¿Quieres volver a intentar?")
-                    .set_buttons(MessageButtons::YesNo)
-                    .show();
-
-                let continuar = matches!(continuar, MessageDialogResult::Yes);
+                let continuar = ask_yes_no(
+                    textos::VOLVER_A_INTENTAR,
+                    textos::VOLVER_A_INTENTAR_DESCRIPCION.get(),
+                );
 
                 if continuar {
                     continue;
@@ -793,7 +968,7 @@ fn get_directorio_salida(salida: Option<PathBuf>) -> Result<PathBuf, ExitCode> {
             }
 
             if !salida.is_dir() {
-                println!("Error: La ruta de salida no es un directorio");
+                println!("{}: {}", msg::ERROR, textos::SALIDA_NO_DIRECTORIO);
                 return Err(ExitCode::FAILURE);
             }
         }
@@ -837,18 +1012,13 @@ fn iter_dir_files<'a>(
         .filter_map(|a| {
             let Ok(a) = a else {
                 if FROM_CLI.load(Ordering::Relaxed) {
-                    eprintln!("Error: {}", a.err().unwrap());
+                    eprintln!("{}: {}", msg::ERROR, a.err().unwrap());
                     #[cfg(target_os = "windows")]
-                    eprintln!("Si la ruta la especificaste como `.\\directorio\\` intenta especificarlo como `.\\directorio`, eliminando la última diagonal inversa \\");
+                    eprintln!("{}", textos::RUTA_WINDOWS);
                 } else {
-                    let msg = format!("Hubo un error con la ruta especificada {}", ruta.display());
-
-                    MessageDialog::new()
-                        .set_title("Error")
-                        .set_level(MessageLevel::Error)
-                        .set_description(msg)
-                        .show()
-                    ;
+                    let error = textos::ERROR_RUTA.fill(ruta.display());
+
+                    show_message(Level::Error, msg::ERROR.get(), &error);
                 }
                 return None;
             };
@@ -919,4 +1089,13 @@ mod tests {
 
         assert_eq!(super::vec_differences(&a, &b), 3);
     }
+
+    #[test]
+    fn group_name() {
+        use std::path::Path;
+
+        for entrada in ["Datos_MTY_22-23.xlsx", "Datos_GDL_22-24.xlsx", "Datos.xlsx"] {
+            assert_eq!(super::get_name(Path::new(entrada)), "Datos", "{entrada}");
+        }
+    }
 }
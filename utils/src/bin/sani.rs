@@ -1,16 +1,331 @@
-use clap::Parser;
-use strings::*;
+use clap::{Parser, ValueEnum};
+use std::{
+    fs::File,
+    io::{self, BufRead, BufReader, BufWriter, Write},
+    path::PathBuf,
+    process::ExitCode,
+};
+use strings::{get_words_ext, names::FullName, phonetic_key, Normalizer};
+use thiserror::Error;
 
+/// Sanitize the text of the arguments, or the lines of files or stdin
 #[derive(Parser)]
 struct Args {
+    /// Text to sanitize as a single line. If empty, the lines of `--file` or stdin are sanitized
     input: Vec<String>,
+    /// Files to read line by line, `-` is stdin
+    #[clap(short, long)]
+    file: Vec<PathBuf>,
+    /// Write the result back to each file instead of printing it
+    #[clap(short, long, requires = "file")]
+    in_place: bool,
+    /// What to write for each value
+    #[clap(short, long, value_enum, default_value_t = Mode::Text)]
+    mode: Mode,
+    /// Chars that also separate words. Defaults to `,` for plain lines and to none for CSV fields.
+    /// The name modes ignore it, they use the comma to tell surnames from given names
+    #[clap(short, long)]
+    seps: Option<String>,
+    /// Read the input as CSV and sanitize every field, or only the fields of `--column`. Quoted
+    /// fields may have delimiters, quotes and line breaks
+    #[clap(long)]
+    csv: bool,
+    /// Fields to sanitize by their position starting at 1 or by their header. Implies `--csv`
+    #[clap(short, long)]
+    column: Vec<String>,
+    /// Delimiter of the CSV fields, an ASCII char
+    #[clap(short, long, default_value_t = ',')]
+    delimiter: char,
+    /// Keep the fields of the first record as they are. Implied when a column is given by its
+    /// header
+    #[clap(long)]
+    header: bool,
+    /// Chars of each word in the `initials` mode
+    #[clap(short = 'n', long, default_value_t = 1)]
+    initials: usize,
 }
 
-fn main() {
-    let Args { input } = Args::parse();
-    let contents = input.join(" ");
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Mode {
+    /// Words joined with a single space, keeping case and accents
+    Spaces,
+    /// Lowercase words without accents joined with a single space
+    Text,
+    /// Canonical key of a personal name
+    NameKey,
+    /// Spanish phonetic code of the words
+    Phonetic,
+    /// Lowercase ASCII words joined with `-`
+    Slug,
+    /// First chars of the first given name and of each surname
+    Initials,
+}
+
+#[derive(Error, Debug)]
+enum SaniError {
+    #[error("{0}")]
+    Io(#[from] io::Error),
+    #[error("{0}")]
+    Csv(#[from] csv::Error),
+    #[error("no column `{0}` in the headers")]
+    Column(String),
+    #[error("the delimiter `{0}` is not an ASCII char")]
+    Delimiter(char),
+}
+
+/// How each line is processed, shared by every input
+struct Sanitizer {
+    mode: Mode,
+    seps: Vec<char>,
+    initials: usize,
+    csv: Option<Csv>,
+}
+
+struct Csv {
+    delimiter: u8,
+    header: bool,
+    /// Columns as written in the arguments, resolved with the headers of each input
+    columns: Vec<String>,
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+
+    match run(args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("Error: {err}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(args: Args) -> Result<(), SaniError> {
+    let delimiter = u8::try_from(args.delimiter)
+        .ok()
+        .filter(u8::is_ascii)
+        .ok_or(SaniError::Delimiter(args.delimiter))?;
+    let csv = (args.csv || !args.column.is_empty()).then(|| Csv {
+        delimiter,
+        header: args.header || args.column.iter().any(|c| c.parse::<usize>().is_err()),
+        columns: args.column,
+    });
+    let seps = args
+        .seps
+        .unwrap_or_else(|| if csv.is_some() { "" } else { "," }.to_string());
+
+    let sanitizer = Sanitizer {
+        mode: args.mode,
+        seps: seps.chars().collect(),
+        initials: args.initials,
+        csv,
+    };
+
+    if !args.input.is_empty() {
+        println!("{}", sanitizer.value(&args.input.join(" ")));
+        return Ok(());
+    }
+
+    if args.file.is_empty() {
+        let stdout = io::stdout().lock();
+        return sanitizer.stream(io::stdin().lock(), BufWriter::new(stdout));
+    }
+
+    for path in &args.file {
+        if path.as_os_str() == "-" {
+            let stdout = io::stdout().lock();
+            sanitizer.stream(io::stdin().lock(), BufWriter::new(stdout))?;
+        } else if args.in_place {
+            let mut res = Vec::new();
+            sanitizer.stream(BufReader::new(File::open(path)?), &mut res)?;
+            std::fs::write(path, res)?;
+        } else {
+            let stdout = io::stdout().lock();
+            sanitizer.stream(BufReader::new(File::open(path)?), BufWriter::new(stdout))?;
+        }
+    }
+
+    Ok(())
+}
+
+impl Sanitizer {
+    fn stream(&self, input: impl BufRead, mut output: impl Write) -> Result<(), SaniError> {
+        if let Some(csv) = &self.csv {
+            return self.stream_csv(csv, input, output);
+        }
+
+        for line in input.lines() {
+            writeln!(output, "{}", self.value(&line?))?;
+        }
+
+        output.flush()?;
+        Ok(())
+    }
+
+    /// Sanitize the fields of each CSV record, quoting them again when they need it
+    fn stream_csv(
+        &self,
+        csv: &Csv,
+        input: impl BufRead,
+        output: impl Write,
+    ) -> Result<(), SaniError> {
+        let mut reader = csv::ReaderBuilder::new()
+            .delimiter(csv.delimiter)
+            .has_headers(false)
+            .flexible(true)
+            .from_reader(input);
+        let mut writer = csv::WriterBuilder::new()
+            .delimiter(csv.delimiter)
+            .flexible(true)
+            .terminator(csv::Terminator::Any(b'\n'))
+            .from_writer(output);
+        let mut records = reader.records();
+
+        let columns = if csv.header {
+            let Some(header) = records.next().transpose()? else {
+                return Ok(());
+            };
+            writer.write_record(&header)?;
+            csv.resolve(&header.iter().map(str::to_string).collect::<Vec<_>>())?
+        } else {
+            csv.resolve(&[])?
+        };
+
+        for record in records {
+            let record = record?;
+            let fields = record.iter().enumerate().map(|(i, field)| {
+                if columns.is_empty() || columns.contains(&i) {
+                    self.value(field)
+                } else {
+                    field.to_string()
+                }
+            });
+            writer.write_record(fields)?;
+        }
+
+        writer.flush()?;
+        Ok(())
+    }
+
+    fn value(&self, value: &str) -> String {
+        let words = || {
+            get_words_ext(value, &self.seps)
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+
+        match self.mode {
+            Mode::Spaces => words(),
+            Mode::Text => Normalizer::new().normalize(&words()),
+            Mode::NameKey => FullName::parse(value).key(),
+            Mode::Phonetic => phonetic_key(&words()),
+            Mode::Slug => {
+                let words = words();
+                let text = Normalizer::new().chars(words.chars());
+                let slug = text
+                    .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+                    .collect::<String>();
+                slug.split('-')
+                    .filter(|part| !part.is_empty())
+                    .collect::<Vec<_>>()
+                    .join("-")
+            }
+            Mode::Initials => FullName::parse(value).initials_key(self.initials),
+        }
+    }
+}
+
+impl Csv {
+    /// Indexes of the columns to sanitize, empty if every column must be sanitized
+    fn resolve(&self, headers: &[String]) -> Result<Vec<usize>, SaniError> {
+        self.columns
+            .iter()
+            .map(|column| match column.parse::<usize>() {
+                Ok(n) if n > 0 => Ok(n - 1),
+                _ => headers
+                    .iter()
+                    .position(|h| h.trim() == column.trim())
+                    .ok_or_else(|| SaniError::Column(column.clone())),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sanitizer(mode: Mode) -> Sanitizer {
+        Sanitizer {
+            mode,
+            seps: vec![','],
+            initials: 1,
+            csv: None,
+        }
+    }
+
+    #[test]
+    fn modes() {
+        let value = "  Rodríguez   Pérez,  Juan Pablo ";
+
+        assert_eq!(
+            sanitizer(Mode::Spaces).value(value),
+            "Rodríguez Pérez Juan Pablo"
+        );
+        assert_eq!(
+            sanitizer(Mode::Text).value(value),
+            "rodriguez perez juan pablo"
+        );
+        assert_eq!(
+            sanitizer(Mode::NameKey).value(value),
+            "juan pablo rodriguez perez"
+        );
+        assert_eq!(
+            sanitizer(Mode::Slug).value(value),
+            "rodriguez-perez-juan-pablo"
+        );
+        assert_eq!(sanitizer(Mode::Initials).value(value), "j r p");
+    }
+
+    #[test]
+    fn csv_columns() {
+        let mut sani = sanitizer(Mode::Spaces);
+        sani.seps.clear();
+        sani.csv = Some(Csv {
+            delimiter: b',',
+            header: true,
+            columns: vec!["Nombre".to_string()],
+        });
+
+        let input = "Id,Nombre\n 1 ,\"  Peña ,  Ana \"\n2,Luis  Gómez\n";
+        let mut output = Vec::new();
+        sani.stream(input.as_bytes(), &mut output)
+            .expect("valid CSV");
+
+        assert_eq!(
+            String::from_utf8(output).expect("valid UTF-8"),
+            "Id,Nombre\n 1 ,\"Peña , Ana\"\n2,Luis Gómez\n"
+        );
+    }
+
+    #[test]
+    fn csv_multiline() {
+        let mut sani = sanitizer(Mode::Spaces);
+        sani.seps.clear();
+        sani.csv = Some(Csv {
+            delimiter: b',',
+            header: false,
+            columns: vec!["2".to_string()],
+        });
 
-    let words = sanitize(&contents, &[',']);
+        let input = "1,\"Ana\n  \"\"Peña\"\"\",\"Calle 1,\nCentro\"\n2,Luis,\n";
+        let mut output = Vec::new();
+        sani.stream(input.as_bytes(), &mut output)
+            .expect("valid CSV");
 
-    println!("{}", String::from_iter(words))
+        assert_eq!(
+            String::from_utf8(output).expect("valid UTF-8"),
+            "1,\"Ana \"\"Peña\"\"\",\"Calle 1,\nCentro\"\n2,Luis,\n"
+        );
+    }
 }